@@ -0,0 +1,51 @@
+use logibaba::entity::EntityRepository;
+use logibaba::level_map::LevelMap;
+use logibaba::lint::{lint, WIN_BUDGET};
+
+const USAGE: &str = "usage: logibaba-lint [--budget <states>] [level file...]";
+
+// Checks levels for authoring mistakes and prints each one as file:line.
+// Without any level files every level in the assets folder is checked.
+fn main() {
+    let entity_repo = match EntityRepository::new() {
+        Ok(entity_repo) => entity_repo,
+        Err(error) => {
+            eprintln!("Could not load {}: {}", EntityRepository::PATH, error);
+            std::process::exit(1);
+        }
+    };
+
+    let mut paths: Vec<String> = std::env::args().skip(1).collect();
+    let mut budget = WIN_BUDGET;
+    if paths.first().is_some_and(|flag| flag == "--budget") {
+        let Some(states) = paths.get(1).and_then(|states| states.parse::<usize>().ok()) else {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        };
+        budget = states;
+        paths.drain(..2);
+    }
+    if paths.is_empty() {
+        paths = (1..).take_while(|level| LevelMap::exists(*level)).map(LevelMap::path).collect();
+    }
+
+    let mut clean = true;
+    for path in paths {
+        match lint(&path, &entity_repo, budget) {
+            Ok(problems) => {
+                for problem in problems.iter() {
+                    println!("{}", problem);
+                }
+                clean &= problems.is_empty();
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                clean = false;
+            }
+        }
+    }
+
+    if !clean {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,59 @@
+use logibaba::entity::EntityRepository;
+use logibaba::level_map::LevelMap;
+use logibaba::replay::encode_turns;
+use logibaba::solver::{solve, Solution};
+use logibaba::world::World;
+
+// States explored before giving up, unless --budget says otherwise
+const DEFAULT_BUDGET: usize = 200_000;
+
+const USAGE: &str = "usage: logibaba-solve <level number or file> [--budget <states>]";
+
+// Prints the shortest sequence of turns that wins a level, in the same
+// notation as replay files.
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (level, budget) = match args.as_slice() {
+        [level] => (level, Some(DEFAULT_BUDGET)),
+        [level, flag, budget] if flag == "--budget" => (level, budget.parse::<usize>().ok()),
+        _ => (&String::new(), None),
+    };
+    let Some(budget) = budget.filter(|_| !level.is_empty()) else {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    };
+
+    let entity_repo = match EntityRepository::new() {
+        Ok(entity_repo) => entity_repo,
+        Err(error) => {
+            eprintln!("Could not load {}: {}", EntityRepository::PATH, error);
+            std::process::exit(1);
+        }
+    };
+
+    // Either a level number or the path to a level file
+    let path = match level.parse::<i32>() {
+        Ok(number) => LevelMap::path(number),
+        Err(_) => level.clone(),
+    };
+    let level_map = match LevelMap::load(&path, &entity_repo) {
+        Ok(level_map) => level_map,
+        Err(error) => {
+            eprintln!("Could not load level {}: {}", path, error);
+            std::process::exit(1);
+        }
+    };
+
+    let world = World::new(level_map.entities, level_map.grid_size, level_map.edges, entity_repo);
+    match solve(&world, budget) {
+        Solution::Solved(turns) => println!("Solved in {} turns: {}", turns.len(), encode_turns(&turns)),
+        Solution::Unsolvable => {
+            println!("Unsolvable");
+            std::process::exit(1);
+        }
+        Solution::OutOfBudget => {
+            println!("No solution found within {} states", budget);
+            std::process::exit(1);
+        }
+    }
+}
@@ -7,7 +7,7 @@ use sdl2::ttf::{ Font, Sdl2TtfContext };
 use lazy_static::lazy_static;
 
 lazy_static! {
-    static ref TTF_CONTEXT: Sdl2TtfContext = sdl2::ttf::init().unwrap();
+    pub(crate) static ref TTF_CONTEXT: Sdl2TtfContext = sdl2::ttf::init().unwrap();
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -94,6 +94,20 @@ impl<'a> DebugConsole<'a> {
                     debug_text.texture = None;  // Force the texture to be updated in the next draw call
                 }
             }
+
+            // Drop lines left over from a longer version of this output
+            self.text_map.retain(|text_key, _| {
+                let (text_key, key) = match (text_key, &key) {
+                    (DebugKey::Entity(text_key), DebugKey::Entity(key)) => (text_key, key),
+                    (DebugKey::Rules(text_key), DebugKey::Rules(key)) => (text_key, key),
+                    _ => return true,
+                };
+                text_key
+                    .strip_prefix(key.as_str())
+                    .and_then(|suffix| suffix.strip_prefix('_'))
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .is_none_or(|index| index < lines.len())
+            });
         }
     }
     
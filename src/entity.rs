@@ -1,17 +1,15 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
 
-use sdl2::rect::Rect;
-
-use crate::logibaba::MovementDirection;
+use crate::world::MovementDirection;
 
 #[derive(Debug, Clone)]
 pub struct SpriteData {
     pub sprite_sheet: String,
     pub frame_width: u32,
     pub frame_height: u32,
-    pub sprite_width: u32,
-    pub sprite_height: u32,
-    pub start_frame: Rect,
+    pub start_frame: (i32, i32),
     pub frame_x: i32,
     pub frame_y: i32,
     pub num_frames: u32,
@@ -23,291 +21,441 @@ pub enum EntityState {
     You,
     Win,
     Push,
+    Pull,
     Move,
     Stop,
+    Defeat,
+    Sink,
+    Hot,
+    Melt,
+    Open,
+    Shut,
+    Up,
+    Down,
+    Left,
+    Right,
+    Shift,
+    Float,
+    Word,
     Active,
 }
 
-#[derive(Debug, Clone)]
+impl EntityState {
+    pub fn from_name(name: &str) -> Option<EntityState> {
+        match name {
+            "You" => Some(EntityState::You),
+            "Win" => Some(EntityState::Win),
+            "Push" => Some(EntityState::Push),
+            "Pull" => Some(EntityState::Pull),
+            "Move" => Some(EntityState::Move),
+            "Stop" => Some(EntityState::Stop),
+            "Defeat" => Some(EntityState::Defeat),
+            "Sink" => Some(EntityState::Sink),
+            "Hot" => Some(EntityState::Hot),
+            "Melt" => Some(EntityState::Melt),
+            "Open" => Some(EntityState::Open),
+            "Shut" => Some(EntityState::Shut),
+            "Up" => Some(EntityState::Up),
+            "Down" => Some(EntityState::Down),
+            "Left" => Some(EntityState::Left),
+            "Right" => Some(EntityState::Right),
+            "Shift" => Some(EntityState::Shift),
+            "Float" => Some(EntityState::Float),
+            "Word" => Some(EntityState::Word),
+            "Active" => Some(EntityState::Active),
+            _ => None,
+        }
+    }
+}
+
+// What an entity is on the board: a plain object, or a word that can be read
+// as part of a rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntityKind {
+    Object,
+    Noun,
+    Operator,
+    Property,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationStyle {
+    // Always shows its start frame
+    Static,
+    // Walk cycle that follows the movement direction
+    Directional,
+}
+
+// Who is on each of the eight tiles around an entity
+#[derive(Debug, Clone, Default)]
 pub struct Neighbors {
     pub up: Option<HashSet<usize>>,
+    pub up_right: Option<HashSet<usize>>,
     pub right: Option<HashSet<usize>>,
+    pub down_right: Option<HashSet<usize>>,
     pub down: Option<HashSet<usize>>,
+    pub down_left: Option<HashSet<usize>>,
     pub left: Option<HashSet<usize>>,
+    pub up_left: Option<HashSet<usize>>,
 }
 
 impl Neighbors {
     pub fn new() -> Self {
         Self {
             up: None,
+            up_right: None,
             right: None,
+            down_right: None,
             down: None,
+            down_left: None,
             left: None,
+            up_left: None,
         }
     }
+
+    pub fn toward(&self, direction: MovementDirection) -> Option<&HashSet<usize>> {
+        match direction {
+            MovementDirection::Up => self.up.as_ref(),
+            MovementDirection::Right => self.right.as_ref(),
+            MovementDirection::Down => self.down.as_ref(),
+            MovementDirection::Left => self.left.as_ref(),
+            MovementDirection::Idle => None,
+        }
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &HashSet<usize>> {
+        [
+            &self.up,
+            &self.up_right,
+            &self.right,
+            &self.down_right,
+            &self.down,
+            &self.down_left,
+            &self.left,
+            &self.up_left,
+        ]
+        .into_iter()
+        .flatten()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Entity {
+    // Stays the same for as long as the entity exists, however entities are
+    // reordered or removed around it. Handed out by the world.
+    pub id: usize,
     pub name: String,
+    pub kind: EntityKind,
+    pub word: String,
     pub states: HashMap<EntityState, bool>,
+    pub default_states: HashMap<EntityState, bool>,
     pub position: (i32, i32),
     pub tile: (i32, i32),
     pub neighbors: Neighbors,
     pub draw_order: i32,
     pub sprite_data: SpriteData,
+    pub animation: AnimationStyle,
     pub movement_direction: MovementDirection,
     pub facing: MovementDirection,
     pub speed: f32,
 }
 
 impl Entity {
+    pub fn is_text(&self) -> bool {
+        self.kind != EntityKind::Object
+    }
+
+    // Floating entities only interact with other floating entities, and
+    // grounded ones with grounded ones
+    pub fn is_floating(&self) -> bool {
+        self.states.contains_key(&EntityState::Float)
+    }
+
     pub fn tile_to_position(&mut self, tile_width: i32, tile_height: i32) {
-        if self.tile.0 > 11 {
-            self.tile.0 = 0;
-        }
-        if self.tile.0 < 0 {
-            self.tile.0 = 11;
-        }
-        if self.tile.1 > 7 {
-            self.tile.1 = 0;
-        }
-        if self.tile.1 < 0 {
-            self.tile.1 = 7;
-        }
         self.position = (self.tile.0 * (tile_width), self.tile.1 * (tile_height));
     }
 }
 
+#[derive(Debug)]
+pub enum EntityError {
+    Io { path: String, error: std::io::Error },
+    Parse { line: usize, message: String },
+    SpriteSheet { path: String, message: String },
+}
+
+impl fmt::Display for EntityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntityError::Io { path, error } => write!(f, "could not read entities {}: {}", path, error),
+            EntityError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            EntityError::SpriteSheet { path, message } => write!(f, "could not load sprite sheet {}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for EntityError {}
+
+// key -> (line, value) for one archetype section
+type Fields = HashMap<String, (usize, String)>;
+
+// Every key an archetype section can set
+const KEYS: [&str; 10] = [
+    "kind",
+    "word",
+    "states",
+    "sprite_sheet",
+    "frame_size",
+    "start_frame",
+    "frames",
+    "draw_order",
+    "animation",
+    "speed",
+];
+
+// Entity archetypes are defined in a plain text file, one `[Name]` section per
+// archetype followed by `key = value` lines:
+//
+//     [Logi]
+//     kind = object
+//     sprite_sheet = ./assets/spritesheets/characters.png
+//     start_frame = 576, 1
+//     frames = 12
+//     draw_order = 3
+//     animation = directional
+//
+// Words also name what they stand for with `word`, which defaults to the
+// archetype name. `states` are the ones an entity has with no rules applying
+// to it. `states`, `frame_size`, `speed` and `animation` are optional.
+#[derive(Debug, Clone)]
 pub struct EntityRepository {
-    tile_width: u32,
-    tile_height: u32,
     predefined_entities: HashMap<String, Entity>,
+    // A number for each archetype name, in name order
+    name_ids: HashMap<String, usize>,
 }
 
 impl EntityRepository {
-    pub fn new(tile_width: u32, tile_height: u32) -> Self {
+    pub const PATH: &'static str = "./assets/entities.txt";
+
+    pub fn new() -> Result<Self, EntityError> {
+        EntityRepository::load(EntityRepository::PATH)
+    }
+
+    pub fn load(path: &str) -> Result<Self, EntityError> {
+        let source = fs::read_to_string(path).map_err(|error| EntityError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+
+        EntityRepository::parse(&source)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, EntityError> {
         let mut predefined_entities = HashMap::new();
-        
-        let position = (0, 0);
-        let tile = (0, 0);
-        let frame_width = 24;
-        let frame_height = 24;
-        let sprite_width = tile_width;
-        let sprite_height = tile_height;
-        let current_frame = 0;
-
-        predefined_entities.insert(
-            "Logi".to_string(),
-            Entity {
-                name: "Logi".to_string(),
-                states: vec![(EntityState::You, true)].into_iter().collect(),
-                position,
-                tile,
-                neighbors: Neighbors::new(),
-                draw_order: 3,
-                sprite_data: SpriteData {
-                    sprite_sheet: "./assets/spritesheets/characters.png".to_string(),
-                    frame_width,
-                    frame_height,
-                    sprite_width,
-                    sprite_height,
-                    start_frame: Rect::new(576, 1, 24, 24),
-                    frame_x: 576,
-                    frame_y: 1,
-                    num_frames: 12,
-                    current_frame,
-                },
-                movement_direction: MovementDirection::Idle,
-                facing: MovementDirection::Right,
-                speed: 1.0,
-            },
-        );
-
-        predefined_entities.insert(
-            "Goal".to_string(),
-            Entity {
-                name: "Goal".to_string(),
-                states: vec![(EntityState::Win, true)].into_iter().collect(),
-                position,
-                tile,
-                neighbors: Neighbors::new(),
-                draw_order: 1,
-                sprite_data: SpriteData {
-                    sprite_sheet: "./assets/spritesheets/objects.png".to_string(),
-                    frame_width,
-                    frame_height,
-                    sprite_width,
-                    sprite_height,
-                    start_frame: Rect::new(101, 226, 24, 24),
-                    frame_x: 101,
-                    frame_y: 226,
-                    num_frames: 1,
-                    current_frame,
-                },
-                movement_direction: MovementDirection::Idle,
-                facing: MovementDirection::Right,
-                speed: 0.0,
-            },
-        );
-
-        predefined_entities.insert(
-            "LogiText".to_string(),
-            Entity {
-                name: "Logitext".to_string(),
-                states: vec![(EntityState::Active, false)].into_iter().collect(),
-                position,
-                tile,
-                neighbors: Neighbors::new(),
-                draw_order: 2,
-                sprite_data: SpriteData {
-                    sprite_sheet: "./assets/spritesheets/characters.png".to_string(),
-                    frame_width,
-                    frame_height,
-                    sprite_width,
-                    sprite_height,
-                    start_frame: Rect::new(526, 1, 24, 24),
-                    frame_x: 526,
-                    frame_y: 1,
-                    num_frames: 2,
-                    current_frame,
-                },
-                movement_direction: MovementDirection::Idle,
-                facing: MovementDirection::Right,
-                speed: 1.0,
-            },
-        );
-
-        predefined_entities.insert(
-            "GoalText".to_string(),
-            Entity {
-                name: "GoalText".to_string(),
-                states: vec![(EntityState::Active, false)].into_iter().collect(),
-                position,
-                tile,
-                neighbors: Neighbors::new(),
-                draw_order: 2,
-                sprite_data: SpriteData {
-                    sprite_sheet: "./assets/spritesheets/objects.png".to_string(),
-                    frame_width,
-                    frame_height,
-                    sprite_width,
-                    sprite_height,
-                    start_frame: Rect::new(51, 226, 24, 24),
-                    frame_x: 51,
-                    frame_y: 226,
-                    num_frames: 2,
-                    current_frame,
-                },
-                movement_direction: MovementDirection::Idle,
-                facing: MovementDirection::Right,
-                speed: 1.0,
-            },
-        );
-
-        predefined_entities.insert(
-            "Is".to_string(),
-            Entity {
-                name: "Is".to_string(),
-                states: vec![(EntityState::Active, false)].into_iter().collect(),
-                position,
-                tile,
-                neighbors: Neighbors::new(),
-                draw_order: 2,
-                sprite_data: SpriteData {
-                    sprite_sheet: "./assets/spritesheets/text-entities.png".to_string(),
-                    frame_width,
-                    frame_height,
-                    sprite_width,
-                    sprite_height,
-                    start_frame: Rect::new(251, 76, 24, 24),
-                    frame_x: 251,
-                    frame_y: 76,
-                    num_frames: 2,
-                    current_frame,
-                },
-                movement_direction: MovementDirection::Idle,
-                facing: MovementDirection::Right,
-                speed: 1.0,
-            },
-        );
-
-        predefined_entities.insert(
-            "Push".to_string(),
-            Entity {
-                name: "Push".to_string(),
-                states: vec![(EntityState::Active, false)].into_iter().collect(),
-                position,
-                tile,
-                neighbors: Neighbors::new(),
-                draw_order: 2,
-                sprite_data: SpriteData {
-                    sprite_sheet: "./assets/spritesheets/text-entities.png".to_string(),
-                    frame_width,
-                    frame_height,
-                    sprite_width,
-                    sprite_height,
-                    start_frame: Rect::new(26, 301, 24, 24),
-                    frame_x: 26,
-                    frame_y: 301,
-                    num_frames: 2,
-                    current_frame,
-                },
-                movement_direction: MovementDirection::Idle,
-                facing: MovementDirection::Right,
-                speed: 1.0,
-            },
-        );
-
-        predefined_entities.insert(
-            "Stop".to_string(),
-            Entity {
-                name: "Stop".to_string(),
-                states: vec![(EntityState::Active, false)].into_iter().collect(),
-                position,
-                tile,
-                neighbors: Neighbors::new(),
-                draw_order: 2,
-                sprite_data: SpriteData {
-                    sprite_sheet: "./assets/spritesheets/text-entities.png".to_string(),
-                    frame_width,
-                    frame_height,
-                    sprite_width,
-                    sprite_height,
-                    start_frame: Rect::new(176, 301, 24, 24),
-                    frame_x: 176,
-                    frame_y: 301,
-                    num_frames: 2,
-                    current_frame,
-                },
-                movement_direction: MovementDirection::Idle,
-                facing: MovementDirection::Right,
-                speed: 1.0,
-            },
-        );
-        
-        Self {
-            tile_width,
-            tile_height,
+        let mut section: Option<(usize, String, Fields)> = None;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                if let Some((start, name, fields)) = section.take() {
+                    let entity = Self::archetype(start, &name, &fields)?;
+                    predefined_entities.insert(name, entity);
+                }
+
+                let name = name.trim().to_string();
+                if name.is_empty() || predefined_entities.contains_key(&name) {
+                    return Err(EntityError::Parse {
+                        line: line_number,
+                        message: format!("'{}' is not a new archetype name", name),
+                    });
+                }
+                section = Some((line_number, name, HashMap::new()));
+                continue;
+            }
+
+            let Some((_, _, fields)) = section.as_mut() else {
+                return Err(EntityError::Parse {
+                    line: line_number,
+                    message: "expected an [Archetype] header".to_string(),
+                });
+            };
+            let (key, value) = line.split_once('=').ok_or_else(|| EntityError::Parse {
+                line: line_number,
+                message: format!("expected 'key = value', found '{}'", line),
+            })?;
+            let key = key.trim();
+            if !KEYS.contains(&key) {
+                return Err(EntityError::Parse {
+                    line: line_number,
+                    message: format!("unknown key '{}'", key),
+                });
+            }
+            if let Some((line, _)) = fields.get(key) {
+                return Err(EntityError::Parse {
+                    line: line_number,
+                    message: format!("'{}' is already set on line {}", key, line),
+                });
+            }
+            fields.insert(key.to_string(), (line_number, value.trim().to_string()));
+        }
+
+        if let Some((start, name, fields)) = section.take() {
+            let entity = Self::archetype(start, &name, &fields)?;
+            predefined_entities.insert(name, entity);
+        }
+
+        let mut names: Vec<&String> = predefined_entities.keys().collect();
+        names.sort();
+        let name_ids = names.into_iter().enumerate().map(|(id, name)| (name.clone(), id)).collect();
+
+        Ok(Self {
             predefined_entities,
+            name_ids,
+        })
+    }
+
+    fn archetype(start: usize, name: &str, fields: &Fields) -> Result<Entity, EntityError> {
+        let error = |line: usize, message: String| EntityError::Parse { line, message };
+        let required = |key: &str| {
+            fields
+                .get(key)
+                .map(|(line, value)| (*line, value.as_str()))
+                .ok_or_else(|| error(start, format!("'{}' is missing '{}'", name, key)))
+        };
+        let optional = |key: &str, default: &'static str| {
+            fields
+                .get(key)
+                .map(|(line, value)| (*line, value.as_str()))
+                .unwrap_or((start, default))
+        };
+        let pair = |(line, value): (usize, &str), separator: char| {
+            value
+                .split_once(separator)
+                .and_then(|(a, b)| Some((a.trim().parse::<u32>().ok()?, b.trim().parse::<u32>().ok()?)))
+                .ok_or_else(|| error(line, format!("expected two numbers separated by '{}', found '{}'", separator, value)))
+        };
+        let integer = |(line, value): (usize, &str)| {
+            value
+                .parse::<i32>()
+                .map_err(|_| error(line, format!("expected a whole number, found '{}'", value)))
+        };
+
+        let (line, kind) = required("kind")?;
+        let kind = match kind {
+            "object" => EntityKind::Object,
+            "noun" => EntityKind::Noun,
+            "operator" => EntityKind::Operator,
+            "property" => EntityKind::Property,
+            other => return Err(error(line, format!("unknown kind '{}'", other))),
+        };
+
+        let (line, word) = optional("word", "");
+        let word = if word.is_empty() { name } else { word };
+        if kind == EntityKind::Property && EntityState::from_name(word).is_none() {
+            return Err(error(line, format!("unknown property '{}'", word)));
+        }
+
+        let (line, animation) = optional("animation", "static");
+        let animation = match animation {
+            "static" => AnimationStyle::Static,
+            "directional" => AnimationStyle::Directional,
+            other => return Err(error(line, format!("unknown animation '{}'", other))),
+        };
+
+        let (line, states) = optional("states", "");
+        let states = states
+            .split(',')
+            .map(|state| state.trim())
+            .filter(|state| !state.is_empty())
+            .map(|state| {
+                // Words start out unlit, everything else is simply on
+                EntityState::from_name(state)
+                    .map(|state| (state, state != EntityState::Active))
+                    .ok_or_else(|| error(line, format!("unknown state '{}'", state)))
+            })
+            .collect::<Result<HashMap<EntityState, bool>, EntityError>>()?;
+
+        let (frame_width, frame_height) = pair(optional("frame_size", "24x24"), 'x')?;
+        let (frame_x, frame_y) = pair(required("start_frame")?, ',')?;
+        let (line, frames) = required("frames")?;
+        let num_frames = integer((line, frames))?.max(1) as u32;
+        // The frames are split evenly between the four directions
+        if animation == AnimationStyle::Directional && !num_frames.is_multiple_of(4) {
+            return Err(error(line, format!("directional animation needs a multiple of 4 frames, found {}", num_frames)));
         }
+        let draw_order = integer(required("draw_order")?)?;
+        let (line, speed) = optional("speed", "1.0");
+        let speed = speed
+            .parse::<f32>()
+            .map_err(|_| error(line, format!("expected a number, found '{}'", speed)))?;
+
+        Ok(Entity {
+            id: 0,
+            name: name.to_string(),
+            kind,
+            word: word.to_string(),
+            states: states.clone(),
+            default_states: states,
+            position: (0, 0),
+            tile: (0, 0),
+            neighbors: Neighbors::new(),
+            draw_order,
+            sprite_data: SpriteData {
+                sprite_sheet: required("sprite_sheet")?.1.to_string(),
+                frame_width,
+                frame_height,
+                start_frame: (frame_x as i32, frame_y as i32),
+                frame_x: frame_x as i32,
+                frame_y: frame_y as i32,
+                num_frames,
+                current_frame: 0,
+            },
+            animation,
+            movement_direction: MovementDirection::Idle,
+            facing: MovementDirection::Right,
+            speed,
+        })
+    }
+
+    // The noun archetype that spells out `name`, e.g. LogiText for Logi
+    pub fn text_for(&self, name: &str) -> Option<&str> {
+        self.predefined_entities
+            .iter()
+            .find(|(_, entity)| entity.kind == EntityKind::Noun && entity.word == name)
+            .map(|(text, _)| text.as_str())
+    }
+
+    // Every sprite sheet an archetype is drawn from, each named once
+    pub fn sprite_sheets(&self) -> Vec<&str> {
+        let mut sprite_sheets: Vec<&str> = self
+            .predefined_entities
+            .values()
+            .map(|entity| entity.sprite_data.sprite_sheet.as_str())
+            .collect();
+        sprite_sheets.sort();
+        sprite_sheets.dedup();
+        sprite_sheets
+    }
+
+    // A small number standing for the archetype `name`, cheaper to compare
+    // and store than the name itself
+    pub fn name_id(&self, name: &str) -> Option<usize> {
+        self.name_ids.get(name).copied()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.predefined_entities.contains_key(name)
     }
 
-    pub fn create_entity(&self, name: &str, x_pos: i32, y_pos: i32) -> Option<Entity> {
+    pub fn create_entity(&self, name: &str, tile_x: i32, tile_y: i32) -> Option<Entity> {
         self.predefined_entities.get(name).map(|entity| Entity {
-            name: entity.name.clone(),
-            states: entity.states.clone(),
-            position: (x_pos, y_pos),
-            tile: (
-                x_pos / (self.tile_width as i32),
-                y_pos / (self.tile_height as i32),
-            ),
-            neighbors: entity.neighbors.clone(),
-            draw_order: entity.draw_order,
-            sprite_data: entity.sprite_data.clone(),
-            movement_direction: entity.movement_direction.clone(),
-            facing: entity.facing.clone(),
-            speed: entity.speed,
+            position: entity.position,
+            tile: (tile_x, tile_y),
+            ..entity.clone()
         })
     }
 }
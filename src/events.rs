@@ -1,26 +1,22 @@
-use logibaba::MovementDirection;
+use std::time::Instant;
 
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 use sdl2::{event::Event, keyboard::Keycode, mouse::MouseButton, EventPump};
 
 use crate::debug_console::DebugConsole;
-use crate::entity::{Entity, EntityState};
-use crate::level_map::LevelMap;
-use crate::logibaba;
+use crate::input::InputState;
+use crate::world::{Input, MovementDirection};
 
 pub struct Events;
 
 impl Events {
     pub fn process_events(
-        entities: &mut Vec<Entity>,
+        input: &mut InputState,
         event_pump: &mut EventPump,
         canvas: &mut Canvas<Window>,
         debug_console: &mut DebugConsole,
     ) -> bool {
-        let mut movement_direction = None;
-        let mut facing = None;
-
         for event in event_pump.poll_iter() {
             match event {
                 Event::Quit { .. }
@@ -44,50 +40,21 @@ impl Events {
                     mouse_btn: MouseButton::Left,
                     ..
                 } => {}
+                // Key repeat is handled by InputState, ignore the OS one
+                Event::KeyDown { repeat: true, .. } => {}
                 Event::KeyDown {
                     keycode: Some(keycode),
                     ..
                 } => match keycode {
-                    Keycode::W | Keycode::Up => {
-                        movement_direction = Some(MovementDirection::Up);
-                        facing = Some(MovementDirection::Up);
-                    }
-                    Keycode::A | Keycode::Left => {
-                        movement_direction = Some(MovementDirection::Left);
-                        facing = Some(MovementDirection::Left);
-                    }
-                    Keycode::S | Keycode::Down => {
-                        movement_direction = Some(MovementDirection::Down);
-                        facing = Some(MovementDirection::Down);
-                    }
-                    Keycode::D | Keycode::Right => {
-                        movement_direction = Some(MovementDirection::Right);
-                        facing = Some(MovementDirection::Right);
+                    Keycode::W | Keycode::Up
+                    | Keycode::A | Keycode::Left
+                    | Keycode::S | Keycode::Down
+                    | Keycode::D | Keycode::Right
+                    | Keycode::Space
+                    | Keycode::Z | Keycode::Backspace
+                    | Keycode::R => {
+                        input.press(Self::input_for(keycode), Instant::now());
                     }
-                    Keycode::R => {
-                        entities.clear();
-                        entities.append(&mut LevelMap::new(2, &canvas).entities);
-                    }
-                    Keycode::P => {
-                        for entity in &mut *entities {
-                            if !entity.states.contains_key(&EntityState::You) {
-                                if !entity.states.contains_key(&EntityState::Push) {
-                                    entity.states.insert(EntityState::Push, true);
-                                } else {
-                                    entity.states.remove(&EntityState::Push);
-                                }
-                            }
-                        }
-                    }
-                    Keycode::Space => {
-                        for entity in &mut *entities {
-                            if !entity.states.contains_key(&EntityState::Stop) {
-                                entity.states.insert(EntityState::Stop, true);
-                            } else {
-                                entity.states.remove(&EntityState::Stop);
-                            }
-                        }                    
-                    }                    
                     Keycode::Backquote => {
                         debug_console.show_console = !debug_console.show_console;
                     }
@@ -97,33 +64,33 @@ impl Events {
                     keycode: Some(keycode),
                     ..
                 } => match keycode {
-                    Keycode::W
-                    | Keycode::A
-                    | Keycode::S
-                    | Keycode::D
-                    | Keycode::Up
-                    | Keycode::Left
-                    | Keycode::Down
-                    | Keycode::Right => {
-                        movement_direction = Some(MovementDirection::Idle);
+                    Keycode::W | Keycode::Up
+                    | Keycode::A | Keycode::Left
+                    | Keycode::S | Keycode::Down
+                    | Keycode::D | Keycode::Right
+                    | Keycode::Space
+                    | Keycode::Z | Keycode::Backspace
+                    | Keycode::R => {
+                        input.release(Self::input_for(keycode));
                     }
                     _ => {}
                 },
                 _ => {}
             }
-
-            if movement_direction.is_some() {
-                for entity in &mut *entities {
-                    if entity.states.contains_key(&EntityState::You) {
-                        entity.movement_direction = movement_direction.unwrap();
-                        if facing.is_some() {
-                            entity.facing = facing.unwrap();
-                        }
-                    }
-                }
-            }
         }
 
-        return true;
+        true
+    }
+
+    fn input_for(keycode: Keycode) -> Input {
+        match keycode {
+            Keycode::W | Keycode::Up => Input::Move(MovementDirection::Up),
+            Keycode::A | Keycode::Left => Input::Move(MovementDirection::Left),
+            Keycode::S | Keycode::Down => Input::Move(MovementDirection::Down),
+            Keycode::D | Keycode::Right => Input::Move(MovementDirection::Right),
+            Keycode::Z | Keycode::Backspace => Input::Undo,
+            Keycode::R => Input::Restart,
+            _ => Input::Wait,
+        }
     }
 }
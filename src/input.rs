@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::world::Input;
+
+// How long a turn key has to be held before it starts repeating, and how
+// often it repeats after that.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyRepeat {
+    pub delay: Duration,
+    pub interval: Duration,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        KeyRepeat {
+            delay: Duration::from_millis(250),
+            interval: Duration::from_millis(150),
+        }
+    }
+}
+
+// Turns requested by the player that haven't been simulated yet. Every key
+// press queues exactly one turn, so taps are never lost between frames. The
+// caller passes in the time so repeats don't depend on a real clock.
+pub struct InputState {
+    pub turns: VecDeque<Input>,
+    pub key_repeat: Option<KeyRepeat>,
+    held: Option<(Input, Instant)>,
+}
+
+impl InputState {
+    pub fn new(key_repeat: Option<KeyRepeat>) -> InputState {
+        InputState {
+            turns: VecDeque::new(),
+            key_repeat,
+            held: None,
+        }
+    }
+
+    pub fn press(&mut self, input: Input, now: Instant) {
+        self.turns.push_back(input);
+
+        // Holding restart shouldn't restart over and over
+        self.held = match input {
+            Input::Restart => None,
+            _ => self
+                .key_repeat
+                .map(|key_repeat| (input, now + key_repeat.delay)),
+        };
+    }
+
+    pub fn release(&mut self, input: Input) {
+        if self.held.is_some_and(|(held_input, _)| held_input == input) {
+            self.held = None;
+        }
+    }
+
+    // The next turn to simulate, either a queued key press or a repeat of the
+    // key that is still held down.
+    pub fn next_turn(&mut self, now: Instant) -> Option<Input> {
+        if let Some(input) = self.turns.pop_front() {
+            return Some(input);
+        }
+
+        let (input, next_repeat) = self.held?;
+        let key_repeat = self.key_repeat?;
+        if now < next_repeat {
+            return None;
+        }
+
+        self.held = Some((input, next_repeat + key_repeat.interval));
+        Some(input)
+    }
+}
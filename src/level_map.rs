@@ -1,77 +1,241 @@
 use std::collections::HashMap;
-use std::collections::HashSet;
-
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 
 use crate::entity::{ Entity, EntityRepository };
+use crate::world::EdgeBehavior;
+
+// Levels are plain text files made of a metadata header, a legend mapping
+// characters to entity names, and the grid itself:
+//
+//     title: First Steps
+//     author: CCappsDevelopment
+//     size: 12x8
+//     edges: solid
+//
+//     legend:
+//     L = Logi
+//     X = Logi + Goal
+//
+//     grid:
+//     L...........
+//
+// `.` and spaces are empty tiles, and lines starting with `#` before the grid
+// are comments. `edges` is either `wrap` or `solid` and defaults to `wrap`.
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io { path: String, error: std::io::Error },
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io { path, error } => write!(f, "could not read level {}: {}", path, error),
+            LevelError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+#[derive(PartialEq)]
+enum Section {
+    Header,
+    Legend,
+    Grid,
+}
 
 pub struct LevelMap {
+    pub title: String,
+    pub author: String,
+    pub grid_size: (i32, i32),
+    pub edges: EdgeBehavior,
     pub entities: Vec<Entity>,
-    pub entity_map: HashMap<(i32, i32), HashSet<usize>>,
 }
 
 impl LevelMap {
-    pub fn new(level_to_load: i32, canvas: &Canvas<Window>) -> LevelMap {
-        let loaded_map_entities = LevelMap::get_level_map(level_to_load, &canvas);
-        let mut entity_map = HashMap::new();
-        for (index, entity) in loaded_map_entities.iter().enumerate() {
-            let entity_position = (entity.tile.0, entity.tile.1);
-            let mut entity_set = HashSet::new();
-            entity_set.insert(index);
-            entity_map.insert(entity_position, entity_set);
-        }
+    pub fn new(level_to_load: i32, entity_repo: &EntityRepository) -> Result<LevelMap, LevelError> {
+        LevelMap::load(&LevelMap::path(level_to_load), entity_repo)
+    }
 
-        LevelMap {
-            entities: loaded_map_entities,
-            entity_map,
-        }
+    pub fn path(level: i32) -> String {
+        format!("./assets/levels/level_{}.txt", level)
+    }
+
+    pub fn exists(level: i32) -> bool {
+        Path::new(&LevelMap::path(level)).exists()
+    }
+
+    pub fn load(path: &str, entity_repo: &EntityRepository) -> Result<LevelMap, LevelError> {
+        let source = fs::read_to_string(path).map_err(|error| LevelError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+
+        LevelMap::parse(&source, entity_repo)
+    }
+
+    pub fn parse(source: &str, entity_repo: &EntityRepository) -> Result<LevelMap, LevelError> {
+        LevelMap::parse_with(source, entity_repo, false)
     }
 
-    fn get_level_map(level: i32, canvas: &Canvas<Window>) -> Vec<Entity> {
-        let tile_width = canvas.viewport().width() / 12;
-        let tile_height = canvas.viewport().height() / 8;
+    // Like `parse`, but entities the repository doesn't know are left out
+    // instead of failing, for the linter, which reports those itself
+    pub(crate) fn parse_skipping_unknown(source: &str, entity_repo: &EntityRepository) -> Result<LevelMap, LevelError> {
+        LevelMap::parse_with(source, entity_repo, true)
+    }
 
+    fn parse_with(source: &str, entity_repo: &EntityRepository, skip_unknown: bool) -> Result<LevelMap, LevelError> {
+        let mut title = String::new();
+        let mut author = String::new();
+        let mut grid_size = None;
+        let mut edges = EdgeBehavior::Wrap;
+        let mut legend: HashMap<char, Vec<String>> = HashMap::new();
         let mut entities = Vec::new();
-        let entity_repo = EntityRepository::new(tile_width, tile_height);
-
-        match level {
-            1 => {
-                entities = vec![
-                    entity_repo.create_entity("Logi", 0, 0).unwrap(),
-                    // entity_repo
-                    //     .create_entity("Goal", 5 * (tile_width as i32), 5 * (tile_height as i32))
-                    //     .unwrap()
-                ];
+
+        let mut section = Section::Header;
+        let mut grid_start = 0;
+        let mut rows = 0;
+
+        for (i, raw_line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let error = |message: String| LevelError::Parse { line: line_number, message };
+
+            if section == Section::Grid {
+                // Spaces are tiles too, so only a stray carriage return goes
+                let line = raw_line.trim_end_matches('\r');
+                if line.is_empty() {
+                    continue;
+                }
+
+                let (width, height) = grid_size.ok_or_else(|| error("grid before size".to_string()))?;
+                if rows >= height {
+                    return Err(error(format!("more than {} rows in the grid", height)));
+                }
+                if line.chars().count() as i32 != width {
+                    return Err(error(format!("row is {} tiles wide, expected {}", line.chars().count(), width)));
+                }
+
+                for (x, symbol) in line.chars().enumerate() {
+                    if symbol == '.' || symbol == ' ' {
+                        continue;
+                    }
+
+                    let names = legend
+                        .get(&symbol)
+                        .ok_or_else(|| error(format!("'{}' is not in the legend", symbol)))?;
+                    for name in names {
+                        match entity_repo.create_entity(name, x as i32, rows) {
+                            Some(entity) => entities.push(entity),
+                            None if skip_unknown => {}
+                            None => return Err(error(format!("unknown entity '{}'", name))),
+                        }
+                    }
+                }
+
+                rows += 1;
+                continue;
             }
-            2 => {
-                entities = vec![
-                    entity_repo
-                        .create_entity("Logi", 0 * (tile_width as i32), 0 * (tile_height as i32))
-                        .unwrap(),
-                    entity_repo
-                        .create_entity("Goal", 0 * (tile_width as i32), 6 * (tile_height as i32))
-                        .unwrap(),
-                    entity_repo
-                        .create_entity("LogiText", 3 * (tile_width as i32), 1 * (tile_height as i32))
-                        .unwrap(),
-                    entity_repo
-                        .create_entity("GoalText", 3 * (tile_width as i32), 3 * (tile_height as i32))
-                        .unwrap(),
-                    entity_repo
-                        .create_entity("Stop", 6 * (tile_width as i32), 1 * (tile_height as i32))
-                        .unwrap(),
-                    entity_repo
-                        .create_entity("Push", 6 * (tile_width as i32), 3 * (tile_height as i32))
-                        .unwrap(),
-                    entity_repo
-                        .create_entity("Is", 5 * (tile_width as i32), 4 * (tile_height as i32))
-                        .unwrap()
-                ];
+
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line {
+                "legend:" => {
+                    section = Section::Legend;
+                    continue;
+                }
+                "grid:" => {
+                    section = Section::Grid;
+                    grid_start = line_number;
+                    continue;
+                }
+                _ => {}
+            }
+
+            match section {
+                Section::Header => {
+                    let (key, value) = line
+                        .split_once(':')
+                        .ok_or_else(|| error(format!("expected 'key: value', found '{}'", line)))?;
+                    let value = value.trim();
+
+                    match key.trim() {
+                        "title" => title = value.to_string(),
+                        "author" => author = value.to_string(),
+                        "size" => {
+                            let size = value
+                                .split_once('x')
+                                .and_then(|(width, height)| {
+                                    Some((width.trim().parse::<i32>().ok()?, height.trim().parse::<i32>().ok()?))
+                                })
+                                .filter(|(width, height)| *width > 0 && *height > 0)
+                                .ok_or_else(|| error(format!("size should look like 12x8, found '{}'", value)))?;
+                            grid_size = Some(size);
+                        }
+                        "edges" => {
+                            edges = match value {
+                                "wrap" => EdgeBehavior::Wrap,
+                                "solid" => EdgeBehavior::Solid,
+                                other => return Err(error(format!("edges should be wrap or solid, found '{}'", other))),
+                            };
+                        }
+                        other => return Err(error(format!("unknown header '{}'", other))),
+                    }
+                }
+                Section::Legend => {
+                    let (symbol, names) = line
+                        .split_once('=')
+                        .ok_or_else(|| error(format!("expected 'X = Name', found '{}'", line)))?;
+
+                    let mut symbol_chars = symbol.trim().chars();
+                    let symbol = match (symbol_chars.next(), symbol_chars.next()) {
+                        (Some(symbol), None) if symbol != '.' => symbol,
+                        _ => return Err(error(format!("legend key '{}' should be a single character", symbol.trim()))),
+                    };
+
+                    let names: Vec<String> = names.split('+').map(|name| name.trim().to_string()).collect();
+                    if names.iter().any(|name| name.is_empty()) {
+                        return Err(error(format!("legend entry for '{}' is missing a name", symbol)));
+                    }
+                    if legend.insert(symbol, names).is_some() {
+                        return Err(error(format!("'{}' is in the legend twice", symbol)));
+                    }
+                }
+                Section::Grid => unreachable!(),
             }
-            _ => {}
         }
+
+        let grid_size = grid_size.ok_or(LevelError::Parse {
+            line: 1,
+            message: "missing 'size' header".to_string(),
+        })?;
+        if section != Section::Grid {
+            return Err(LevelError::Parse {
+                line: source.lines().count(),
+                message: "missing 'grid:' section".to_string(),
+            });
+        }
+        if rows != grid_size.1 {
+            return Err(LevelError::Parse {
+                line: grid_start,
+                message: format!("grid has {} rows, expected {}", rows, grid_size.1),
+            });
+        }
+
         entities.sort_by_key(|entity| entity.draw_order);
-        return entities;
+
+        Ok(LevelMap {
+            title,
+            author,
+            grid_size,
+            edges,
+            entities,
+        })
     }
 }
@@ -1,11 +1,26 @@
+#[cfg(feature = "sdl")]
 pub mod sdl_context;
+#[cfg(feature = "sdl")]
 pub mod screen_renderer;
 pub mod level_map;
 pub mod entity;
+#[cfg(feature = "sdl")]
 pub mod debug_console;
+pub mod rules;
+pub mod world;
+pub mod replay;
+pub mod solver;
+pub mod lint;
 
+#[cfg(feature = "sdl")]
 mod logibaba;
+#[cfg(feature = "sdl")]
 mod events;
+mod input;
 
+#[cfg(feature = "sdl")]
 pub use logibaba::Game;
+#[cfg(feature = "sdl")]
 pub use events::Events;
+pub use input::{InputState, KeyRepeat};
+pub use world::MovementDirection;
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+
+use crate::entity::{Entity, EntityKind, EntityRepository, EntityState};
+use crate::level_map::{LevelError, LevelMap};
+use crate::rules::Word;
+use crate::solver::{solve, Solution};
+use crate::world::World;
+
+// States the linter explores looking for a win before taking the level's
+// word for it, unless told otherwise
+pub const WIN_BUDGET: usize = 20_000;
+
+// One authoring mistake in a level, and the line it is on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub path: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path, self.line, self.message)
+    }
+}
+
+// Where things are in the level source, so problems found on the parsed
+// level can point back at it
+struct Lines {
+    legend: Vec<(usize, Vec<String>)>,
+    grid: usize,
+    rows: Vec<usize>,
+}
+
+impl Lines {
+    fn scan(source: &str) -> Lines {
+        let mut lines = Lines {
+            legend: Vec::new(),
+            grid: 1,
+            rows: Vec::new(),
+        };
+        let mut in_legend = false;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            if lines.grid > 1 {
+                if !line.trim_end_matches('\r').is_empty() {
+                    lines.rows.push(line_number);
+                }
+                continue;
+            }
+
+            match line.trim() {
+                "legend:" => in_legend = true,
+                "grid:" => lines.grid = line_number,
+                line if in_legend && !line.starts_with('#') => {
+                    if let Some((_, names)) = line.split_once('=') {
+                        let names = names.split('+').map(|name| name.trim().to_string()).collect();
+                        lines.legend.push((line_number, names));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        lines
+    }
+
+    fn row(&self, y: i32) -> usize {
+        self.rows.get(y as usize).copied().unwrap_or(self.grid)
+    }
+}
+
+pub fn lint(path: &str, entity_repo: &EntityRepository, win_budget: usize) -> Result<Vec<Problem>, LevelError> {
+    let source = fs::read_to_string(path).map_err(|error| LevelError::Io {
+        path: path.to_string(),
+        error,
+    })?;
+
+    Ok(lint_source(path, &source, entity_repo, win_budget))
+}
+
+// Everything wrong with a level, in the order it appears in the file. Looking
+// for a win gives up after `win_budget` states.
+pub fn lint_source(path: &str, source: &str, entity_repo: &EntityRepository, win_budget: usize) -> Vec<Problem> {
+    let lines = Lines::scan(source);
+    let mut problems = Vec::new();
+    let mut problem = |line: usize, message: String| {
+        problems.push(Problem {
+            path: path.to_string(),
+            line,
+            message,
+        })
+    };
+
+    // Report typos at the legend line they're on rather than wherever the
+    // grid first uses them
+    let mut typos = false;
+    for (line, names) in lines.legend.iter() {
+        for name in names.iter().filter(|name| !name.is_empty() && !entity_repo.contains(name)) {
+            problem(*line, format!("unknown entity '{}'", name));
+            typos = true;
+        }
+    }
+
+    // Unknown names were reported above, so leave them out to find the rest
+    let level_map = match LevelMap::parse_skipping_unknown(source, entity_repo) {
+        Ok(level_map) => level_map,
+        Err(LevelError::Parse { line, message }) => {
+            problem(line, message);
+            return sorted(problems);
+        }
+        Err(error) => {
+            problem(1, error.to_string());
+            return sorted(problems);
+        }
+    };
+
+    // Without the misspelled entities the board isn't the one that was meant
+    if typos {
+        return sorted(problems);
+    }
+
+    let world = World::new(level_map.entities, level_map.grid_size, level_map.edges, entity_repo.clone());
+    let at = |tile: (i32, i32)| format!("column {}", tile.0 + 1);
+
+    // Entities that destroy each other before the first turn
+    let mut clashes: BTreeMap<(i32, i32), Vec<&str>> = BTreeMap::new();
+    for i in world.doomed() {
+        let tile = world.entities[i].tile;
+        let names = clashes.entry((tile.1, tile.0)).or_default();
+        for j in world.entity_map[&tile].iter() {
+            if !names.contains(&world.entities[*j].name.as_str()) {
+                names.push(&world.entities[*j].name);
+            }
+        }
+    }
+    for ((y, x), mut names) in clashes {
+        names.sort();
+        problem(lines.row(y), format!("{} can't share the tile at {}", names.join(" and "), at((x, y))));
+    }
+
+    let is = |state: EntityState| world.entities.iter().any(|entity| entity.states.contains_key(&state));
+    let words: Vec<Word> = world
+        .entities
+        .iter()
+        .filter(|entity| entity.is_text())
+        .filter_map(Word::from_entity)
+        .collect();
+    let has_word = |matches: fn(&Word) -> bool| words.iter().any(matches);
+
+    let has_you = is(EntityState::You);
+    if !has_you {
+        problem(lines.grid, "nothing is YOU when the level starts".to_string());
+    }
+    if !is(EntityState::Win) && !has_word(|word| *word == Word::Property(EntityState::Win)) {
+        problem(lines.grid, "nothing is WIN and there is no WIN word to change that".to_string());
+    } else if has_you {
+        match solve(&world, win_budget) {
+            Solution::Solved(_) => {}
+            Solution::Unsolvable => problem(lines.grid, "no sequence of turns reaches a win".to_string()),
+            // Not a mistake as far as anyone knows, but the level wasn't checked
+            Solution::OutOfBudget => problem(lines.grid, format!("no win found within {} states; raise --budget", win_budget)),
+        }
+    }
+
+    // Words that no sentence on this board could ever use
+    let no_verb = !has_word(|word| matches!(word, Word::Is | Word::Has | Word::Make));
+    let no_is = !has_word(|word| *word == Word::Is);
+    let no_noun = !has_word(|word| matches!(word, Word::Noun(_)));
+    let mut texts: Vec<&Entity> = world.entities.iter().filter(|entity| entity.is_text()).collect();
+    texts.sort_by_key(|entity| (entity.tile.1, entity.tile.0));
+    for entity in texts {
+        let missing = if no_verb {
+            "IS, HAS or MAKE"
+        } else if no_noun {
+            "noun"
+        } else if no_is && entity.kind == EntityKind::Property {
+            "IS"
+        } else {
+            continue;
+        };
+        problem(
+            lines.row(entity.tile.1),
+            format!("{} at {} is never part of a rule: there is no {} on the board", entity.name, at(entity.tile), missing),
+        );
+    }
+
+    sorted(problems)
+}
+
+fn sorted(mut problems: Vec<Problem>) -> Vec<Problem> {
+    problems.sort_by_key(|problem| problem.line);
+    problems
+}
@@ -1,54 +1,76 @@
 extern crate sdl2;
 
-use std::{
-    collections::{HashMap, HashSet},
-    time::Duration,
-};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
 use events::Events;
+use input::{InputState, KeyRepeat};
 use screen_renderer::ScreenRenderer;
 
-use crate::{entity::Entity, events, level_map::LevelMap, screen_renderer, debug_console::{DebugConsole, DebugKey}};
+use crate::{entity::{EntityError, EntityRepository}, events, input, level_map::{LevelError, LevelMap}, screen_renderer, debug_console::{DebugConsole, DebugKey}, replay::Replay, world::{EdgeBehavior, Input, World, WorldEvent}};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum MovementDirection {
-    Up,
-    Right,
-    Down,
-    Left,
-    Idle,
+// How long the level complete overlay stays up before the next level loads
+const LEVEL_COMPLETE_DELAY: Duration = Duration::from_secs(2);
+
+// Time between turns when playing back a replay
+const REPLAY_TURN_INTERVAL: Duration = Duration::from_millis(150);
+
+// A replay being played back in place of the player's input
+struct Playback {
+    turns: VecDeque<Input>,
+    hash: u64,
+    next_turn_at: Instant,
+    finished: bool,
 }
 
 pub struct Game<'a> {
     pub screen_renderer: ScreenRenderer,
-    pub entities: Vec<Entity>,
-    pub entity_map: HashMap<(i32, i32), HashSet<usize>>,
+    pub world: World,
+    pub input: InputState,
     pub debug_console: DebugConsole<'a>,
+    pub entity_repo: EntityRepository,
+    pub current_level: i32,
+    pub level_completed_at: Option<Instant>,
+    pub replay: Replay,
+    playback: Option<Playback>,
 }
 
 impl<'a> Game<'a> {
-    pub fn new() -> Game<'a> {
-        let screen_renderer = ScreenRenderer::new();
-        let entities = Vec::new();
-        let entity_map = HashMap::new();
+    pub fn new() -> Result<Game<'a>, EntityError> {
+        let entity_repo = EntityRepository::new()?;
+        let screen_renderer = ScreenRenderer::new(&entity_repo)?;
+        let world = World::new(Vec::new(), (12, 8), EdgeBehavior::Wrap, entity_repo.clone());
+        let input = InputState::new(Some(KeyRepeat::default()));
         let debug_console = DebugConsole::new();
+        let current_level = 1;
+        let level_completed_at = None;
+        let replay = Replay::new(current_level);
+        let playback = None;
 
-        Game {
+        Ok(Game {
             screen_renderer,
-            entities,
-            entity_map,
+            world,
+            input,
             debug_console,
-        }
+            entity_repo,
+            current_level,
+            level_completed_at,
+            replay,
+            playback,
+        })
     }
 
     pub fn start(&mut self) {
-        self.load_level(2);
+        if let Err(error) = self.load_level(self.current_level) {
+            eprintln!("Could not load level {}: {}", self.current_level, error);
+            return;
+        }
 
         // Game loop
         'running: loop {
             // Handle events
             if !Events::process_events(
-                &mut self.entities,
+                &mut self.input,
                 &mut self.screen_renderer.context.event_pump,
                 &mut self.screen_renderer.context.canvas,
                 &mut self.debug_console
@@ -56,24 +78,148 @@ impl<'a> Game<'a> {
                 break 'running;
             }
 
+            // Simulate one turn per key press, independent of the frame rate
+            while let Some(input) = self.next_turn() {
+                if self.level_completed_at.is_some() {
+                    continue;
+                }
+
+                let world_events = self.world.apply(input);
+                self.screen_renderer.update(&mut self.world.entities);
+
+                // A replay only plays back its own level
+                if self.playback.is_some() {
+                    continue;
+                }
+                self.replay.record(input);
+
+                if world_events.contains(&WorldEvent::LevelComplete) {
+                    self.complete_level();
+                }
+            }
+
+            if self.level_completed_at.is_some_and(|completed_at| completed_at.elapsed() >= LEVEL_COMPLETE_DELAY) {
+                self.next_level();
+            }
+
             // Render the screen
             self.screen_renderer
-                .draw(&mut self.entities, &mut self.entity_map, &mut self.debug_console);
+                .draw(&mut self.world, &mut self.debug_console);
 
             // Set the framerate to 60fps
             std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
         }
+
+        // Keep what the player did in a level they didn't finish as well
+        if self.playback.is_none() && self.level_completed_at.is_none() && !self.replay.turns.is_empty() {
+            self.save_replay();
+        }
+    }
+
+    // Load the replay's level and play its turns back instead of taking
+    // input from the player
+    pub fn play_replay(&mut self, replay: Replay) {
+        if replay.version != Replay::VERSION {
+            eprintln!("Replay was recorded with version {}, this is {}", replay.version, Replay::VERSION);
+        }
+
+        self.current_level = replay.level;
+        self.playback = Some(Playback {
+            turns: replay.turns.into(),
+            hash: replay.hash,
+            next_turn_at: Instant::now() + REPLAY_TURN_INTERVAL,
+            finished: false,
+        });
+        self.start();
+    }
+
+    // The next turn to simulate, from the replay being played back if there
+    // is one and from the player otherwise
+    fn next_turn(&mut self) -> Option<Input> {
+        let Some(playback) = self.playback.as_mut() else {
+            return self.input.next_turn(Instant::now());
+        };
+
+        // The player only watches a replay
+        while self.input.next_turn(Instant::now()).is_some() {}
+
+        if playback.finished || Instant::now() < playback.next_turn_at {
+            return None;
+        }
+        playback.next_turn_at = Instant::now() + REPLAY_TURN_INTERVAL;
+
+        if let Some(input) = playback.turns.pop_front() {
+            return Some(input);
+        }
+
+        // Out of turns, so check the board ended up where it was recorded
+        playback.finished = true;
+        let hash = self.world.state_hash();
+        self.screen_renderer.overlay = Some(if hash == playback.hash {
+            "Replay matches".to_string()
+        } else {
+            eprintln!("Replay diverged: expected board {:016x}, got {:016x}", playback.hash, hash);
+            "Replay diverged!".to_string()
+        });
+
+        None
+    }
+
+    // Save the turns that got the player to where they are in this level
+    fn save_replay(&mut self) {
+        self.replay.finish(&self.world);
+
+        let path = Replay::path(self.current_level);
+        if let Err(error) = self.replay.save(&path) {
+            eprintln!("Could not save replay {}: {}", path, error);
+        }
     }
 
-    pub fn load_level(&mut self, level_to_load: i32) {
-        let level_map = LevelMap::new(level_to_load, &self.screen_renderer.context.canvas);
-        self.entities = level_map.entities;
-        self.entity_map = level_map.entity_map;
+    fn complete_level(&mut self) {
+        self.save_replay();
+        self.level_completed_at = Some(Instant::now());
+        self.screen_renderer.overlay = Some(if LevelMap::exists(self.current_level + 1) {
+            "Level complete!".to_string()
+        } else {
+            "All levels complete!".to_string()
+        });
+    }
+
+    // Load the level after the current one. After the last level the
+    // overlay simply stays up.
+    fn next_level(&mut self) {
+        if !LevelMap::exists(self.current_level + 1) {
+            return;
+        }
+
+        if let Err(error) = self.load_level(self.current_level + 1) {
+            eprintln!("Could not load level {}: {}", self.current_level + 1, error);
+            self.screen_renderer.overlay = Some(format!("Could not load level {}", self.current_level + 1));
+            self.level_completed_at = None;
+        }
+    }
+
+    pub fn load_level(&mut self, level_to_load: i32) -> Result<(), LevelError> {
+        let level_map = LevelMap::new(level_to_load, &self.entity_repo)?;
+        self.world = World::new(level_map.entities, level_map.grid_size, level_map.edges, self.entity_repo.clone());
+        self.screen_renderer.set_grid_size(level_map.grid_size);
+        self.screen_renderer.update(&mut self.world.entities);
+        self.current_level = level_to_load;
+        self.replay = Replay::new(level_to_load);
+        self.level_completed_at = None;
+        self.screen_renderer.overlay = None;
     
-        let level_str = format!("Level: {:?}", level_to_load);
+        let level_str = format!("Level: {:?} {:?} by {:?}", level_to_load, level_map.title, level_map.author);
         self.debug_console.out(vec![(DebugKey::Entity("1_Level".to_string()), level_str)]);
 
-        self.screen_renderer.debug_console_out(&mut self.debug_console, &self.entities, &self.entity_map);
+        self.screen_renderer.debug_console_out(&mut self.debug_console, &self.world.entities, &self.world.entity_map, &self.world.rules);
+
+        // A level can be won before the first turn, and no turn would report it
+        if self.playback.is_none() && self.world.is_won() {
+            self.complete_level();
+        }
+
+        Ok(())
     }
     
 }
@@ -1,6 +1,23 @@
+use logibaba::entity::EntityRepository;
+use logibaba::replay::Replay;
 use logibaba::Game;
 
 fn main() {
-    let mut game = Game::new();
-    game.start();
+    let mut game = match Game::new() {
+        Ok(game) => game,
+        Err(error) => {
+            eprintln!("Could not load {}: {}", EntityRepository::PATH, error);
+            return;
+        }
+    };
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => game.start(),
+        [flag, path] if flag == "--replay" => match Replay::load(path) {
+            Ok(replay) => game.play_replay(replay),
+            Err(error) => eprintln!("Could not load replay {}: {}", path, error),
+        },
+        _ => eprintln!("usage: logibaba [--replay <file>]"),
+    }
 }
@@ -0,0 +1,200 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::world::MovementDirection;
+use crate::world::{Input, World};
+
+// Every turn a player took in one level, enough to play it back exactly.
+// Replays are small text files:
+//
+//     version: 0.1.0
+//     level: 2
+//     hash: 9f3c0e1d2b4a6c58
+//     turns: RRUUWZX
+//
+// Each turn is one character: U, R, D and L for moves, W to wait, Z to undo
+// and X to restart. `hash` is the world's state hash after the last turn, so
+// a playback that ends up anywhere else can be flagged.
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io { path: String, error: std::io::Error },
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io { path, error } => write!(f, "could not access replay {}: {}", path, error),
+            ReplayError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub version: String,
+    pub level: i32,
+    pub turns: Vec<Input>,
+    pub hash: u64,
+}
+
+impl Replay {
+    // Replays are stamped with the game version they were recorded with
+    pub const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    pub fn new(level: i32) -> Replay {
+        Replay {
+            version: Replay::VERSION.to_string(),
+            level,
+            turns: Vec::new(),
+            hash: 0,
+        }
+    }
+
+    pub fn path(level: i32) -> String {
+        format!("./replays/level_{}.txt", level)
+    }
+
+    pub fn record(&mut self, input: Input) {
+        self.turns.push(input);
+    }
+
+    // Stamp the replay with the state the recorded turns left `world` in
+    pub fn finish(&mut self, world: &World) {
+        self.hash = world.state_hash();
+    }
+
+    // Simulate every recorded turn on `world`, which should be the level as
+    // it was loaded. Returns whether it ended up in the recorded state.
+    pub fn play(&self, world: &mut World) -> bool {
+        for input in &self.turns {
+            world.apply(*input);
+        }
+
+        world.state_hash() == self.hash
+    }
+
+    pub fn load(path: &str) -> Result<Replay, ReplayError> {
+        let source = fs::read_to_string(path).map_err(|error| ReplayError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+
+        Replay::parse(&source)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), ReplayError> {
+        let io_error = |error| ReplayError::Io {
+            path: path.to_string(),
+            error,
+        };
+
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(path, self.to_string()).map_err(io_error)
+    }
+
+    pub fn parse(source: &str) -> Result<Replay, ReplayError> {
+        let mut version = None;
+        let mut level = None;
+        let mut hash = None;
+        let mut turns = None;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let error = |message: String| ReplayError::Parse { line: line_number, message };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| error(format!("expected 'key: value', found '{}'", line)))?;
+            let value = value.trim();
+
+            match key.trim() {
+                "version" => version = Some(value.to_string()),
+                "level" => {
+                    level = Some(
+                        value
+                            .parse::<i32>()
+                            .map_err(|_| error(format!("expected a level number, found '{}'", value)))?,
+                    )
+                }
+                "hash" => {
+                    hash = Some(
+                        u64::from_str_radix(value, 16)
+                            .map_err(|_| error(format!("expected a hex hash, found '{}'", value)))?,
+                    )
+                }
+                "turns" => {
+                    turns = Some(
+                        value
+                            .chars()
+                            .map(|turn| input_for(turn).ok_or_else(|| error(format!("unknown turn '{}'", turn))))
+                            .collect::<Result<Vec<Input>, ReplayError>>()?,
+                    )
+                }
+                other => return Err(error(format!("unknown key '{}'", other))),
+            }
+        }
+
+        let missing = |key: &str| ReplayError::Parse {
+            line: source.lines().count(),
+            message: format!("missing '{}'", key),
+        };
+
+        Ok(Replay {
+            version: version.ok_or_else(|| missing("version"))?,
+            level: level.ok_or_else(|| missing("level"))?,
+            turns: turns.unwrap_or_default(),
+            hash: hash.ok_or_else(|| missing("hash"))?,
+        })
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "level: {}", self.level)?;
+        writeln!(f, "hash: {:016x}", self.hash)?;
+        writeln!(f, "turns: {}", encode_turns(&self.turns))
+    }
+}
+
+// Turns written the way replay files store them, e.g. RRUUW
+pub fn encode_turns(turns: &[Input]) -> String {
+    turns.iter().map(|input| char_for(*input)).collect()
+}
+
+fn char_for(input: Input) -> char {
+    match input {
+        Input::Move(MovementDirection::Up) => 'U',
+        Input::Move(MovementDirection::Right) => 'R',
+        Input::Move(MovementDirection::Down) => 'D',
+        Input::Move(MovementDirection::Left) => 'L',
+        Input::Move(MovementDirection::Idle) | Input::Wait => 'W',
+        Input::Undo => 'Z',
+        Input::Restart => 'X',
+    }
+}
+
+fn input_for(turn: char) -> Option<Input> {
+    match turn {
+        'U' => Some(Input::Move(MovementDirection::Up)),
+        'R' => Some(Input::Move(MovementDirection::Right)),
+        'D' => Some(Input::Move(MovementDirection::Down)),
+        'L' => Some(Input::Move(MovementDirection::Left)),
+        'W' => Some(Input::Wait),
+        'Z' => Some(Input::Undo),
+        'X' => Some(Input::Restart),
+        _ => None,
+    }
+}
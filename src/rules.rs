@@ -0,0 +1,722 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::entity::{Entity, EntityKind, EntityState};
+
+// A word a text entity contributes when read as part of a sentence.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Word {
+    Noun(String),
+    Is,
+    Has,
+    Make,
+    And,
+    Not,
+    On,
+    Near,
+    Facing,
+    Lonely,
+    Property(EntityState),
+}
+
+// The noun that stands for every text entity
+pub const TEXT: &str = "Text";
+
+// Whether `noun` in a rule refers to `entity`
+pub fn is_noun(entity: &Entity, noun: &str) -> bool {
+    if noun == TEXT {
+        entity.is_text()
+    } else {
+        entity.name == noun
+    }
+}
+
+impl Word {
+    // Objects that are WORD read as their own name
+    pub fn from_entity(entity: &Entity) -> Option<Word> {
+        match entity.kind {
+            EntityKind::Object => entity
+                .states
+                .contains_key(&EntityState::Word)
+                .then(|| Word::Noun(entity.name.clone())),
+            EntityKind::Noun => Some(Word::Noun(entity.word.clone())),
+            EntityKind::Operator => match entity.word.as_str() {
+                "Is" => Some(Word::Is),
+                "Has" => Some(Word::Has),
+                "Make" => Some(Word::Make),
+                "And" => Some(Word::And),
+                "Not" => Some(Word::Not),
+                "On" => Some(Word::On),
+                "Near" => Some(Word::Near),
+                "Facing" => Some(Word::Facing),
+                "Lonely" => Some(Word::Lonely),
+                _ => None,
+            },
+            EntityKind::Property => EntityState::from_name(&entity.word).map(Word::Property),
+        }
+    }
+}
+
+// What a rule says its subject is
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    Property(EntityState),
+    Noun(String),
+}
+
+impl Target {
+    // Whether `entity` is the noun, or has the property, this names
+    pub fn matches(&self, entity: &Entity) -> bool {
+        match self {
+            Target::Property(property) => entity.states.contains_key(property),
+            Target::Noun(noun) => is_noun(entity, noun),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Property(property) => write!(f, "{}", format!("{:?}", property).to_uppercase()),
+            Target::Noun(noun) => write!(f, "{}", noun.to_uppercase()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConditionKind {
+    // Nothing else on the same tile
+    Lonely,
+    // Sharing a tile with the target
+    On(Target),
+    // The target is on the same tile or any of the eight around it
+    Near(Target),
+    // The target is on the next tile in the direction the entity faces
+    Facing(Target),
+}
+
+// Something that has to be true of an entity for a rule to apply to it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Condition {
+    pub kind: ConditionKind,
+    pub negated: bool,
+}
+
+impl Condition {
+    pub fn holds(&self, index: usize, entities: &[Entity], entity_map: &HashMap<(i32, i32), HashSet<usize>>) -> bool {
+        let entity = &entities[index];
+        let on_tile: Vec<usize> = entity_map
+            .get(&entity.tile)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|i| *i != index)
+            .collect();
+        let any_matches = |target: &Target, indices: &[usize]| indices.iter().any(|i| target.matches(&entities[*i]));
+
+        let holds = match &self.kind {
+            ConditionKind::Lonely => on_tile.is_empty(),
+            ConditionKind::On(target) => any_matches(target, &on_tile),
+            ConditionKind::Near(target) => {
+                let mut near = on_tile.clone();
+                near.extend(entity.neighbors.all().flatten());
+                any_matches(target, &near)
+            }
+            ConditionKind::Facing(target) => {
+                let facing: Vec<usize> = entity.neighbors.toward(entity.facing).into_iter().flatten().copied().collect();
+                any_matches(target, &facing)
+            }
+        };
+
+        holds != self.negated
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negated {
+            write!(f, "NOT ")?;
+        }
+        match &self.kind {
+            ConditionKind::Lonely => write!(f, "LONELY"),
+            ConditionKind::On(target) => write!(f, "ON {}", target),
+            ConditionKind::Near(target) => write!(f, "NEAR {}", target),
+            ConditionKind::Facing(target) => write!(f, "FACING {}", target),
+        }
+    }
+}
+
+// How a rule's subject relates to its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verb {
+    // Gets the property, or turns into the noun
+    Is,
+    // Leaves the noun behind when destroyed
+    Has,
+    // Puts the noun on its tile every turn
+    Make,
+}
+
+impl Verb {
+    fn from_word(word: &Word) -> Option<Verb> {
+        match word {
+            Word::Is => Some(Verb::Is),
+            Word::Has => Some(Verb::Has),
+            Word::Make => Some(Verb::Make),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Verb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_uppercase())
+    }
+}
+
+// NOUN IS PROPERTY, NOUN IS NOUN, NOUN HAS NOUN or NOUN MAKE NOUN, either
+// side of which can be negated. NOT LOGI is every object except Logi, and IS
+// NOT takes away the target. The rule only applies to subjects that meet all
+// of its conditions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rule {
+    pub subject: String,
+    pub subject_negated: bool,
+    pub conditions: Vec<Condition>,
+    pub verb: Verb,
+    pub target: Target,
+    pub negated: bool,
+}
+
+impl Rule {
+    pub fn applies_to(&self, index: usize, entities: &[Entity], entity_map: &HashMap<(i32, i32), HashSet<usize>>) -> bool {
+        let entity = &entities[index];
+        let is_subject = if self.subject_negated {
+            !entity.is_text() && !is_noun(entity, &self.subject)
+        } else {
+            is_noun(entity, &self.subject)
+        };
+
+        is_subject
+            && self
+                .conditions
+                .iter()
+                .all(|condition| condition.holds(index, entities, entity_map))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let not = |negated: bool| if negated { "NOT " } else { "" };
+        let (prefixes, infixes): (Vec<&Condition>, Vec<&Condition>) = self
+            .conditions
+            .iter()
+            .partition(|condition| condition.kind == ConditionKind::Lonely);
+
+        for condition in prefixes {
+            write!(f, "{} ", condition)?;
+        }
+        write!(f, "{}{}", not(self.subject_negated), self.subject.to_uppercase())?;
+        for (i, condition) in infixes.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { " AND " }, condition)?;
+        }
+        write!(f, " {} {}{}", self.verb, not(self.negated), self.target)
+    }
+}
+
+// Every word on each tile and the id of the entity spelling it
+type Words = HashMap<(i32, i32), Vec<(Word, usize)>>;
+
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+    // Ids of the text entities that are part of a valid sentence
+    pub active: HashSet<usize>,
+    // What the rules were read from
+    words: Option<Words>,
+}
+
+impl RuleSet {
+    // Read every horizontal (left to right) and vertical (top to bottom) run of
+    // text entities on the board and collect the sentences they spell out.
+    // TEXT IS PUSH always holds, so rules can be rearranged unless a sentence
+    // says TEXT IS NOT PUSH.
+    pub fn parse(entities: &[Entity], entity_map: &HashMap<(i32, i32), HashSet<usize>>) -> RuleSet {
+        RuleSet::read(RuleSet::words(entities, entity_map))
+    }
+
+    // Parse the board again, unless it still has the words these rules were
+    // read from. Most turns don't move any.
+    pub fn reparse(&mut self, entities: &[Entity], entity_map: &HashMap<(i32, i32), HashSet<usize>>) {
+        let words = RuleSet::words(entities, entity_map);
+        if self.words.as_ref() != Some(&words) {
+            *self = RuleSet::read(words);
+        }
+    }
+
+    fn words(entities: &[Entity], entity_map: &HashMap<(i32, i32), HashSet<usize>>) -> Words {
+        let mut stacks = HashMap::new();
+        for (tile, indices) in entity_map {
+            let mut indices: Vec<&usize> = indices.iter().collect();
+            indices.sort();
+            let stack: Vec<(Word, usize)> = indices
+                .into_iter()
+                .filter_map(|idx| entities.get(*idx))
+                .filter_map(|entity| Word::from_entity(entity).map(|word| (word, entity.id)))
+                .collect();
+            if !stack.is_empty() {
+                stacks.insert(*tile, stack);
+            }
+        }
+
+        stacks
+    }
+
+    fn read(stacks: Words) -> RuleSet {
+        let mut rules = vec![Rule {
+            subject: TEXT.to_string(),
+            subject_negated: false,
+            conditions: Vec::new(),
+            verb: Verb::Is,
+            target: Target::Property(EntityState::Push),
+            negated: false,
+        }];
+        let mut active = HashSet::new();
+
+        for direction in [(1, 0), (0, 1)] {
+            let mut starts: Vec<&(i32, i32)> = stacks.keys().collect();
+            starts.sort();
+
+            for start in starts {
+                // Only read a run from its first word
+                let before = (start.0 - direction.0, start.1 - direction.1);
+                if stacks.contains_key(&before) {
+                    continue;
+                }
+
+                // Every sentence is at least three words long
+                let length = (0..)
+                    .take_while(|i| stacks.contains_key(&(start.0 + direction.0 * i, start.1 + direction.1 * i)))
+                    .count();
+                if length < 3 {
+                    continue;
+                }
+
+                // Stacked words each get read, picking one word per tile as
+                // the sentence goes along
+                let run: Vec<&[(Word, usize)]> = (0..length as i32)
+                    .map(|i| stacks[&(start.0 + direction.0 * i, start.1 + direction.1 * i)].as_slice())
+                    .collect();
+                let mut start = 0;
+                while start < run.len() {
+                    match parse_sentence(&run[start..]) {
+                        Some((sentence, ids, subject_len)) => {
+                            for rule in sentence {
+                                if !rules.contains(&rule) {
+                                    rules.push(rule);
+                                }
+                            }
+                            active.extend(ids);
+                            // Nothing before the verb can start a sentence of
+                            // its own, but the targets can: LOGI IS GOAL IS WIN
+                            start += subject_len;
+                        }
+                        None => start += 1,
+                    }
+                }
+            }
+        }
+
+        RuleSet {
+            rules,
+            active,
+            words: Some(stacks),
+        }
+    }
+
+    // Rebuild every entity's states from its archetype's default states plus
+    // whatever the active rules give it. IS NOT always beats IS. Conditions
+    // that look at other entities' properties see the ones unconditional
+    // rules give them, so the order entities are visited in doesn't matter.
+    pub fn apply(&self, entities: &mut [Entity], entity_map: &HashMap<(i32, i32), HashSet<usize>>) {
+        let unconditional: Vec<&Rule> = self.rules.iter().filter(|rule| rule.conditions.is_empty()).collect();
+        for i in 0..entities.len() {
+            let rules = Self::applying(&unconditional, i, entities, entity_map);
+            Self::set_states(&mut entities[i], &rules);
+        }
+
+        if unconditional.len() < self.rules.len() {
+            let all: Vec<&Rule> = self.rules.iter().collect();
+            let applying: Vec<Vec<&Rule>> = (0..entities.len())
+                .map(|i| Self::applying(&all, i, entities, entity_map))
+                .collect();
+            for (entity, rules) in entities.iter_mut().zip(applying) {
+                Self::set_states(entity, &rules);
+            }
+        }
+
+        // Words light up while they're part of a rule
+        for entity in entities.iter_mut() {
+            if let Some(active) = entity.states.get_mut(&EntityState::Active) {
+                *active = self.active.contains(&entity.id);
+            }
+        }
+    }
+
+    fn applying<'r>(
+        rules: &[&'r Rule],
+        index: usize,
+        entities: &[Entity],
+        entity_map: &HashMap<(i32, i32), HashSet<usize>>,
+    ) -> Vec<&'r Rule> {
+        rules
+            .iter()
+            .filter(|rule| rule.applies_to(index, entities, entity_map))
+            .copied()
+            .collect()
+    }
+
+    fn set_states(entity: &mut Entity, rules: &[&Rule]) {
+        entity.states = entity.default_states.clone();
+
+        let rules: Vec<&&Rule> = rules.iter().filter(|rule| rule.verb == Verb::Is).collect();
+        for rule in rules.iter().filter(|rule| !rule.negated) {
+            if let Target::Property(property) = &rule.target {
+                entity.states.insert(*property, true);
+            }
+        }
+        for rule in rules.iter().filter(|rule| rule.negated) {
+            if let Target::Property(property) = &rule.target {
+                entity.states.remove(property);
+            }
+        }
+    }
+
+    // The nouns the entity at `index` turns into this turn. NOUN IS NOUN locks
+    // a noun in place, so anything with that rule never transforms, and NOUN
+    // IS NOT NOUN stops that one transformation.
+    pub fn transformations(
+        &self,
+        index: usize,
+        entities: &[Entity],
+        entity_map: &HashMap<(i32, i32), HashSet<usize>>,
+    ) -> Vec<&str> {
+        let nouns = self.nouns(Verb::Is, index, entities, entity_map);
+        if nouns.iter().any(|noun| is_noun(&entities[index], noun)) {
+            return Vec::new();
+        }
+
+        nouns
+    }
+
+    // The nouns the entity at `index` is linked to by `verb`, leaving out any
+    // that a negated rule takes away again
+    pub fn nouns(
+        &self,
+        verb: Verb,
+        index: usize,
+        entities: &[Entity],
+        entity_map: &HashMap<(i32, i32), HashSet<usize>>,
+    ) -> Vec<&str> {
+        let nouns = |negated: bool| {
+            self.rules
+                .iter()
+                .filter(move |rule| {
+                    rule.verb == verb && rule.negated == negated && rule.applies_to(index, entities, entity_map)
+                })
+                .filter_map(|rule| match &rule.target {
+                    Target::Noun(noun) => Some(noun.as_str()),
+                    Target::Property(_) => None,
+                })
+        };
+
+        let blocked: Vec<&str> = nouns(true).collect();
+        let mut allowed: Vec<&str> = Vec::new();
+        for noun in nouns(false) {
+            if !blocked.contains(&noun) && !allowed.contains(&noun) {
+                allowed.push(noun);
+            }
+        }
+
+        allowed
+    }
+}
+
+// Parse the sentences starting on the first tile of `run`:
+//
+//     [LONELY] SUBJECT (AND SUBJECT)* [CONDITION (AND CONDITION)*] VERB TARGET (AND TARGET)*
+//
+// where subjects are nouns, the verb is IS, HAS or MAKE, targets are nouns or
+// (after IS) properties, conditions are
+// ON, NEAR or FACING followed by a noun or property, and any of them can be
+// preceded by NOT. Further nouns or properties after AND in a condition each
+// get the same condition: LOGI ON GOAL AND ROCK is on both.
+//
+// Tiles are read one at a time, carrying on every reading that one of the
+// stacked words fits, so only words that mean different things branch.
+// Stacked nouns are read together as one list of subjects or targets.
+//
+// Each sentence expands into one rule per subject and target. Also returns
+// the ids of the words used, and how many tiles came before the first verb.
+fn parse_sentence(run: &[&[(Word, usize)]]) -> Option<(Vec<Rule>, Vec<usize>, usize)> {
+    let mut readings = vec![Reading::default()];
+    let mut sentences = Vec::new();
+
+    for (pos, stack) in run.iter().enumerate() {
+        let mut next = Vec::new();
+        for reading in readings {
+            // A sentence only goes on after its targets if more targets follow
+            let complete = match reading.expect {
+                Expect::AndTarget => Some(Rc::new(Reading {
+                    complete: None,
+                    ..reading.clone()
+                })),
+                _ => reading.complete.clone(),
+            };
+            let mut read = reading.read(pos, stack, run.get(pos + 1));
+            for reading in read.iter_mut() {
+                reading.complete = complete.clone();
+            }
+            if read.is_empty() {
+                sentences.extend(complete);
+            }
+            next.extend(read);
+        }
+        readings = next;
+        if readings.is_empty() {
+            break;
+        }
+    }
+    sentences.extend(readings.into_iter().filter_map(|reading| match reading.expect {
+        Expect::AndTarget => Some(Rc::new(reading)),
+        _ => reading.complete,
+    }));
+
+    let mut rules = Vec::new();
+    let mut ids = Vec::new();
+    let mut subject_len = usize::MAX;
+    for sentence in sentences {
+        for rule in sentence.rules() {
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+        }
+        ids.extend(&sentence.ids);
+        subject_len = subject_len.min(sentence.subject_len);
+    }
+
+    (!rules.is_empty()).then_some((rules, ids, subject_len))
+}
+
+// Makes the condition an ON, NEAR or FACING word stands for out of its
+// argument
+type Infix = fn(Target) -> ConditionKind;
+
+// What a sentence needs next to carry on
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Expect {
+    // LONELY or the first subject
+    #[default]
+    Start,
+    Subject,
+    // AND, a condition or the verb
+    AndSubject,
+    Condition,
+    Argument,
+    // AND, or the verb
+    AndCondition,
+    // Another argument for the last condition, or a new condition
+    ConditionOrArgument,
+    Target,
+    // AND and more targets. The sentence is complete here.
+    AndTarget,
+    AnotherTarget,
+}
+
+// A sentence read up to some tile
+#[derive(Clone, Default)]
+struct Reading {
+    expect: Expect,
+    // Whether an odd number of NOTs came right before
+    not: bool,
+    subjects: Vec<(String, bool)>,
+    conditions: Vec<Condition>,
+    // The last condition read and whether it's negated, for AND to repeat
+    condition: Option<(Infix, bool)>,
+    subject_len: usize,
+    verb: Option<Verb>,
+    targets: Vec<(Target, bool)>,
+    ids: Vec<usize>,
+    // The longest complete sentence this one carries on from
+    complete: Option<Rc<Reading>>,
+}
+
+impl Reading {
+    // Every way the sentence goes on with the words stacked on tile `pos`.
+    // `after` is the tile after that, if it has any words.
+    fn read(&self, pos: usize, stack: &[(Word, usize)], after: Option<&&[(Word, usize)]>) -> Vec<Reading> {
+        let mut readings = Vec::new();
+        let mut words: Vec<&Word> = Vec::new();
+        for (word, _) in stack {
+            if !words.contains(&word) {
+                words.push(word);
+            }
+        }
+        // Copies of the same word on a tile are read as one
+        let ids_of = |word: &Word| -> Vec<usize> { stack.iter().filter(|(other, _)| other == word).map(|(_, id)| *id).collect() };
+        let next = |expect: Expect, word: &Word| {
+            let mut reading = self.clone();
+            reading.expect = expect;
+            reading.ids.extend(ids_of(word));
+            reading
+        };
+
+        let noun = |word: &Word| match word {
+            Word::Noun(noun) => Some(noun.clone()),
+            _ => None,
+        };
+        let argument = |word: &Word| match word {
+            Word::Noun(noun) => Some(Target::Noun(noun.clone())),
+            Word::Property(property) => Some(Target::Property(*property)),
+            _ => None,
+        };
+        let infix = |word: &Word| -> Option<Infix> {
+            match word {
+                Word::On => Some(ConditionKind::On),
+                Word::Near => Some(ConditionKind::Near),
+                Word::Facing => Some(ConditionKind::Facing),
+                _ => None,
+            }
+        };
+        let target = |word: &Word| match (self.verb, word) {
+            (_, Word::Noun(noun)) => Some(Target::Noun(noun.clone())),
+            (Some(Verb::Is), Word::Property(property)) => Some(Target::Property(*property)),
+            _ => None,
+        };
+
+        if words.contains(&&Word::Not) {
+            let expect = match self.expect {
+                Expect::AndSubject | Expect::ConditionOrArgument => Some(Expect::Condition),
+                Expect::Start | Expect::Subject | Expect::Condition | Expect::Target | Expect::AnotherTarget => {
+                    Some(self.expect)
+                }
+                _ => None,
+            };
+            if let Some(expect) = expect {
+                let mut reading = next(expect, &Word::Not);
+                reading.not = !self.not;
+                readings.push(reading);
+            }
+        }
+
+        if words.contains(&&Word::And) {
+            let expect = match self.expect {
+                Expect::AndSubject => Some(Expect::Subject),
+                Expect::AndCondition => Some(Expect::ConditionOrArgument),
+                Expect::AndTarget => Some(Expect::AnotherTarget),
+                _ => None,
+            };
+            if let Some(expect) = expect {
+                readings.push(next(expect, &Word::And));
+            }
+        }
+
+        if self.expect == Expect::Start && words.contains(&&Word::Lonely) {
+            let mut reading = next(Expect::Subject, &Word::Lonely);
+            reading.conditions.push(Condition {
+                kind: ConditionKind::Lonely,
+                negated: self.not,
+            });
+            reading.not = false;
+            readings.push(reading);
+        }
+
+        // Stacked nouns are all subjects, and stacked targets all targets
+        let nouns: Vec<&Word> = words.iter().copied().filter(|word| noun(word).is_some()).collect();
+        if matches!(self.expect, Expect::Start | Expect::Subject) && !nouns.is_empty() {
+            let mut reading = self.clone();
+            reading.expect = Expect::AndSubject;
+            reading.not = false;
+            for word in nouns {
+                reading.subjects.extend(noun(word).map(|noun| (noun, self.not)));
+                reading.ids.extend(ids_of(word));
+            }
+            readings.push(reading);
+        }
+
+        // A noun followed by a verb after an AND starts the next sentence
+        // instead: LOGI IS PUSH AND GOAL IS WIN
+        let verb_after = after.is_some_and(|after| after.iter().all(|(word, _)| Verb::from_word(word).is_some()));
+        let targets: Vec<&Word> = words.iter().copied().filter(|word| target(word).is_some()).collect();
+        let reads_targets = match self.expect {
+            Expect::Target => true,
+            Expect::AnotherTarget => !verb_after,
+            _ => false,
+        };
+        if reads_targets && !targets.is_empty() {
+            let mut reading = self.clone();
+            reading.expect = Expect::AndTarget;
+            reading.not = false;
+            for word in targets {
+                reading.targets.extend(target(word).map(|target| (target, self.not)));
+                reading.ids.extend(ids_of(word));
+            }
+            readings.push(reading);
+        }
+
+        for word in words {
+            if let Some(verb) = Verb::from_word(word) {
+                if matches!(self.expect, Expect::AndSubject | Expect::AndCondition) {
+                    let mut reading = next(Expect::Target, word);
+                    reading.verb = Some(verb);
+                    reading.subject_len = pos;
+                    readings.push(reading);
+                }
+            }
+
+            if let Some(kind) = infix(word) {
+                if matches!(self.expect, Expect::AndSubject | Expect::Condition | Expect::ConditionOrArgument) {
+                    let mut reading = next(Expect::Argument, word);
+                    reading.condition = Some((kind, self.not));
+                    reading.not = false;
+                    readings.push(reading);
+                }
+            }
+
+            // Each argument makes a different condition, so these branch
+            if let (Some(argument), Some((kind, negated))) = (argument(word), self.condition) {
+                if matches!(self.expect, Expect::Argument | Expect::ConditionOrArgument) {
+                    let mut reading = next(Expect::AndCondition, word);
+                    reading.conditions.push(Condition {
+                        kind: kind(argument),
+                        negated,
+                    });
+                    readings.push(reading);
+                }
+            }
+        }
+
+        readings
+    }
+
+    fn rules(&self) -> Vec<Rule> {
+        let Some(verb) = self.verb else {
+            return Vec::new();
+        };
+
+        self.subjects
+            .iter()
+            .flat_map(|(subject, subject_negated)| {
+                self.targets.iter().map(move |(target, negated)| Rule {
+                    subject: subject.clone(),
+                    subject_negated: *subject_negated,
+                    conditions: self.conditions.clone(),
+                    verb,
+                    target: target.clone(),
+                    negated: *negated,
+                })
+            })
+            .collect()
+    }
+}
@@ -5,38 +5,49 @@ use sdl2::image::{ InitFlag, Sdl2ImageContext };
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::{BlendMode, Texture};
+use sdl2::ttf::Font;
 
-use crate::debug_console::{DebugConsole, DebugKey};
-use crate::entity::{ Entity, EntityState };
-use crate::logibaba::MovementDirection;
+use crate::debug_console::{DebugConsole, DebugKey, TTF_CONTEXT};
+use crate::entity::{AnimationStyle, Entity, EntityError, EntityRepository, EntityState};
+use crate::world::MovementDirection;
+use crate::rules::RuleSet;
 use crate::sdl_context::SdlContext;
+use crate::world::World;
 
 pub struct ScreenRenderer {
     pub context: SdlContext,
     pub frame_delay: u32,
     pub frame_ticks: u32,
-    pub last_frame_ticks: Instant,
     pub image_context: Sdl2ImageContext,
     pub window_width: i32,
     pub window_height: i32,
     pub tile_width: i32,
     pub tile_height: i32,
+    pub board_offset: (i32, i32),
     pub grid_size: (i32, i32),
     pub grid_texture: Texture,
+    pub overlay_font: Font<'static, 'static>,
+    pub overlay: Option<String>,
+    pub started: Instant,
 }
 
+// How long one bob of a floating entity takes, in seconds
+const FLOAT_BOB_PERIOD: f32 = 1.6;
+
+// Color mod for words that aren't part of any rule
+const INACTIVE_SHADE: u8 = 128;
+
 impl ScreenRenderer {
-    pub fn new() -> ScreenRenderer {
-        let mut context = SdlContext::new();
+    pub fn new(entity_repo: &EntityRepository) -> Result<ScreenRenderer, EntityError> {
+        let context = SdlContext::new(&entity_repo.sprite_sheets())?;
         let frame_delay = 6; // Increase for slower animation.
         let frame_ticks = 0;
         let image_context = sdl2::image::init(InitFlag::PNG | InitFlag::JPG).unwrap();
-        let last_frame_ticks = Instant::now();
         let window_width = context.canvas.viewport().width() as i32;
         let window_height = context.canvas.viewport().height() as i32;
-        let tile_width = window_width / 12;
-        let tile_height = window_height / 8;
         let grid_size = (12, 8);
+        let overlay_font = TTF_CONTEXT.load_font("./assets/fonts/LibreFranklin-Medium.ttf", 32).unwrap();
+        let overlay = None;
 
         let texture_creator = context.canvas.texture_creator();
         let mut grid_texture = texture_creator
@@ -45,57 +56,85 @@ impl ScreenRenderer {
 
         grid_texture.set_blend_mode(BlendMode::Blend);
 
-        // create the grid on the texture
-        context.canvas
-            .with_texture_canvas(&mut grid_texture, |canvas| {
-                canvas.set_draw_color(Color::RGBA(228, 228, 240, 64));
-
-                // Draw vertical lines
-                for x in (0..window_width).step_by((window_width as usize) / 12) {
-                    canvas.draw_line((x as i32, 0), (x as i32, window_height as i32)).unwrap();
-                }
-
-                // Draw horizontal lines
-                for y in (0..window_height).step_by((window_height as usize) / 8) {
-                    canvas.draw_line((0, y as i32), (window_width as i32, y as i32)).unwrap();
-                }
-            })
-            .unwrap();
-
-
-        ScreenRenderer {
+        let mut screen_renderer = ScreenRenderer {
             context,
             frame_delay,
             frame_ticks,
             image_context,
-            last_frame_ticks,
             window_width,
             window_height,
-            tile_width,
-            tile_height,
+            tile_width: 0,
+            tile_height: 0,
+            board_offset: (0, 0),
             grid_size,
             grid_texture,
-        }
+            overlay_font,
+            overlay,
+            started: Instant::now(),
+        };
+        screen_renderer.set_grid_size(grid_size);
+
+        Ok(screen_renderer)
+    }
+
+    // Fit a board of `grid_size` tiles into the window with square tiles,
+    // centered, and redraw the cached grid for it.
+    pub fn set_grid_size(&mut self, grid_size: (i32, i32)) {
+        let tile_size = (self.window_width / grid_size.0).min(self.window_height / grid_size.1).max(1);
+
+        self.grid_size = grid_size;
+        self.tile_width = tile_size;
+        self.tile_height = tile_size;
+        self.board_offset = (
+            (self.window_width - tile_size * grid_size.0) / 2,
+            (self.window_height - tile_size * grid_size.1) / 2,
+        );
+
+        let grid_lines = self.grid_lines();
+        self.context.canvas
+            .with_texture_canvas(&mut self.grid_texture, |canvas| {
+                canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+                canvas.clear();
+                canvas.set_draw_color(Color::RGBA(228, 228, 240, 64));
+
+                for (start, end) in &grid_lines {
+                    canvas.draw_line(*start, *end).unwrap();
+                }
+            })
+            .unwrap();
+    }
+
+    // Start and end points of every vertical and horizontal grid line
+    fn grid_lines(&self) -> Vec<((i32, i32), (i32, i32))> {
+        let (left, top) = self.board_offset;
+        let right = left + self.tile_width * self.grid_size.0;
+        let bottom = top + self.tile_height * self.grid_size.1;
+
+        let vertical = (0..=self.grid_size.0).map(|x| {
+            let x = left + x * self.tile_width;
+            ((x, top), (x, bottom))
+        });
+        let horizontal = (0..=self.grid_size.1).map(|y| {
+            let y = top + y * self.tile_height;
+            ((left, y), (right, y))
+        });
+
+        vertical.chain(horizontal).collect()
     }
 
     // Render the screen
     pub fn draw(
         &mut self,
-        entities: &mut Vec<Entity>,
-        entity_map: &mut HashMap<(i32, i32), HashSet<usize>>,
+        world: &mut World,
         debug_console: &mut DebugConsole,
     ) {
-        if self.last_frame_ticks.elapsed().as_millis() >= 80 {
-            self.update(entities, entity_map, debug_console);
-            self.last_frame_ticks = Instant::now();
-        }
-
         let _ = self.draw_bg();
         let _ = self.draw_grid();
-        let _ = self.draw_entities(entities);
+        let _ = self.draw_entities(&mut world.entities);
+        let _ = self.draw_overlay();
 
         if debug_console.show_console {
-           self.debug_console_out(debug_console, entities, entity_map);
+           self.debug_console_out(debug_console, &world.entities, &world.entity_map, &world.rules);
         }
 
         self.context.canvas.present();
@@ -112,18 +151,8 @@ impl ScreenRenderer {
     fn draw_grid(&mut self) -> Result<(), String> {
         self.context.canvas.set_draw_color(Color::RGBA(228, 228, 240, 64));
 
-        // Draw vertical lines
-        for x in (0..self.window_width).step_by((self.window_width as usize) / 12) {
-            self.context.canvas
-                .draw_line((x as i32, 0), (x as i32, self.window_height as i32))
-                .unwrap();
-        }
-
-        // Draw horizontal lines
-        for y in (0..self.window_height).step_by((self.window_height as usize) / 8) {
-            self.context.canvas
-                .draw_line((0, y as i32), (self.window_width as i32, y as i32))
-                .unwrap();
+        for (start, end) in self.grid_lines() {
+            self.context.canvas.draw_line(start, end).unwrap();
         }
 
         Ok(())
@@ -137,177 +166,97 @@ impl ScreenRenderer {
         Ok(())
     }
 
-    fn draw_entities(&mut self, entities: &mut Vec<Entity>) -> Result<(), String> {
-        for entity in entities.iter_mut() {
-            let texture = &self.context.texture_map.get(&entity.sprite_data.sprite_sheet).unwrap();
+    fn draw_entities(&mut self, entities: &mut [Entity]) -> Result<(), String> {
+        // Entities can change archetype during play, so sort by draw order here
+        // rather than relying on the order they were loaded in. Floating
+        // entities go over everything on the ground.
+        let mut draw_order: Vec<usize> = (0..entities.len()).collect();
+        draw_order.sort_by_key(|i| (entities[*i].is_floating(), entities[*i].draw_order));
+
+        let phase = self.started.elapsed().as_secs_f32() / FLOAT_BOB_PERIOD * std::f32::consts::TAU;
+        let bob = (phase.sin() * self.tile_height as f32 / 12.0).round() as i32;
+
+        for i in draw_order {
+            let entity = &mut entities[i];
+            entity.tile_to_position(self.tile_width, self.tile_height);
+
+            let texture = self.context.texture_map.get_mut(&entity.sprite_data.sprite_sheet).unwrap();
+
+            // Words that aren't part of any rule are drawn dimmed
+            let shade = match entity.states.get(&EntityState::Active) {
+                Some(false) => INACTIVE_SHADE,
+                _ => 255,
+            };
+            texture.set_color_mod(shade, shade, shade);
 
             let sprite_rect = Rect::new(
                 entity.sprite_data.frame_x,
-                entity.sprite_data.start_frame.y(),
+                entity.sprite_data.start_frame.1,
                 entity.sprite_data.frame_width,
                 entity.sprite_data.frame_height
             );
 
+            let bob = if entity.is_floating() { bob } else { 0 };
             let world_rect = Rect::new(
-                entity.position.0,
-                entity.position.1,
-                entity.sprite_data.sprite_width,
-                entity.sprite_data.sprite_height
+                self.board_offset.0 + entity.position.0,
+                self.board_offset.1 + entity.position.1 + bob,
+                self.tile_width as u32,
+                self.tile_height as u32
             );
 
-            self.context.canvas.copy(&texture, Some(sprite_rect), Some(world_rect))?;
+            self.context.canvas.copy(texture, Some(sprite_rect), Some(world_rect))?;
         }
 
         Ok(())
     }
 
-    pub fn update(
-        &mut self,
-        entities: &mut Vec<Entity>,
-        entity_map: &mut HashMap<(i32, i32), HashSet<usize>>,
-        debug_console: &mut DebugConsole,
-    ) {
-        // Update entity positions
-        let new_positions = self.update_positions(entities, debug_console);
-
-        // Update entity map
-        self.update_entity_map(entities, entity_map, new_positions);
-        
-        // Update entity neighbors
-        self.update_neighbors(entities, entity_map);
+    // Dim the board and show a centered message, e.g. once a level is complete
+    fn draw_overlay(&mut self) -> Result<(), String> {
+        let Some(text) = &self.overlay else {
+            return Ok(());
+        };
+
+        self.context.canvas.set_blend_mode(BlendMode::Blend);
+        self.context.canvas.set_draw_color(Color::RGBA(28, 28, 40, 192));
+        self.context.canvas.fill_rect(None)?;
+
+        let surface = self.overlay_font.render(text).blended(Color::WHITE).map_err(|e| e.to_string())?;
+        let texture_creator = self.context.canvas.texture_creator();
+        let texture = texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| e.to_string())?;
+
+        let text_rect = Rect::from_center(
+            (self.window_width / 2, self.window_height / 2),
+            surface.width(),
+            surface.height(),
+        );
+        let result = self.context.canvas.copy(&texture, None, Some(text_rect));
+
+        // Textures aren't freed on drop with unsafe_textures
+        unsafe { texture.destroy() };
+
+        result
+    }
 
+    // Called once after every simulated turn and when a level is loaded
+    pub fn update(&mut self, entities: &mut [Entity]) {
         // Update entity sprite frames
         self.update_sprite_frames(entities);
     }
 
-    fn check_can_move(&self, directional_neighbor: &Option<HashSet<usize>>, entities: &Vec<Entity>) -> bool {
-        return directional_neighbor.as_ref().map_or(false, |neighbor| {
-            neighbor.iter().any(|idx| {
-                entities
-                    .get(*idx)
-                    .map(|e| e.states.contains_key(&EntityState::Stop))
-                    .unwrap_or(false)
-            })
-        })
-    }
-    
-    fn check_is_pushed(&self, entity: &Entity, entities: &Vec<Entity>) -> (bool, Option<MovementDirection>) {
-        if self.check_direction(entity.neighbors.up.as_ref(), MovementDirection::Down, entities) {
-            (true, Some(MovementDirection::Up))
-        } else if self.check_direction(entity.neighbors.right.as_ref(), MovementDirection::Left, entities) {
-            (true, Some(MovementDirection::Right))
-        } else if self.check_direction(entity.neighbors.left.as_ref(), MovementDirection::Right, entities) {
-            (true, Some(MovementDirection::Left))
-        } else if self.check_direction(entity.neighbors.down.as_ref(), MovementDirection::Up, entities) {
-            (true, Some(MovementDirection::Down))
-        } else {
-            (false, None)
-        }
-    }
-
-    fn check_direction(&self, neighbors: Option<&HashSet<usize>>, direction: MovementDirection, entities: &Vec<Entity>) -> bool {
-        neighbors.as_ref().map_or(false, |neighbor| {
-            neighbor.iter().any(|idx| {
-                entities
-                    .get(*idx)
-                    .map(|e| e.movement_direction == direction)
-                    .unwrap_or(false)
-            })
-        })
-    }
-    
-    fn update_positions(&self, entities: &mut Vec<Entity>, debug_console: &mut DebugConsole) -> HashMap<usize, (i32, i32)> {
-        let mut new_positions: HashMap<usize, (i32, i32)> = HashMap::new();
-
-        for (i, entity) in entities.iter().enumerate() {
-            // Only move if there's no STOP entity in the direction of movement
-            let mut can_move = false;
-            if entity.states.contains_key(&EntityState::You) || entity.states.contains_key(&EntityState::Move) {
-                can_move = match entity.movement_direction {
-                    MovementDirection::Up => {
-                        !self.check_can_move(&entity.neighbors.up, &entities)
-                    }
-                    MovementDirection::Right => {
-                        !self.check_can_move(&entity.neighbors.right, &entities)
-                    }
-                    MovementDirection::Down => {
-                        !self.check_can_move(&entity.neighbors.down, &entities)
-                    }
-                    MovementDirection::Left => {
-                        !self.check_can_move(&entity.neighbors.left, &entities)
-                    }
-                    _ => true,
-                };
-            }
-
-            let mut is_pushed = false;
-            let mut push_direction = None;
-            if entity.states.contains_key(&EntityState::Push) && !entity.states.contains_key(&EntityState::Stop){
-                let push_check = self.check_is_pushed(&entity, &entities);
-                is_pushed = push_check.0;
-                if is_pushed {
-                    push_direction = push_check.1;
-                }
+    fn update_sprite_frames(&self, entities: &mut [Entity]) {
+        for entity in entities.iter_mut() {
+            // Words show their second, lit frame while part of a rule
+            if let Some(active) = entity.states.get(&EntityState::Active) {
+                let frame = if *active { 1 } else { 0 }.min(entity.sprite_data.num_frames - 1);
+                entity.sprite_data.current_frame = frame;
+                entity.sprite_data.frame_x =
+                    entity.sprite_data.start_frame.0 + frame as i32 * (entity.sprite_data.frame_width as i32 + 1);
+                continue;
             }
-            
-            let new_tile = 
-                if can_move {
-                    match entity.movement_direction {
-                        MovementDirection::Up => (entity.tile.0, entity.tile.1 - 1),
-                        MovementDirection::Right => (entity.tile.0 + 1, entity.tile.1),
-                        MovementDirection::Down => (entity.tile.0, entity.tile.1 + 1),
-                        MovementDirection::Left => (entity.tile.0 - 1, entity.tile.1),
-                        MovementDirection::Idle => entity.tile,
-                    }
-                } else if is_pushed {
-                    match push_direction {
-                        Some(MovementDirection::Up) => (entity.tile.0, entity.tile.1 + 1),
-                        Some(MovementDirection::Right) => (entity.tile.0 - 1, entity.tile.1),
-                        Some(MovementDirection::Down) => (entity.tile.0, entity.tile.1 - 1),
-                        Some(MovementDirection::Left) => (entity.tile.0 + 1, entity.tile.1),
-                        _ => entity.tile,
-                    }
-                } else {
-                    entity.tile
-                };
-
 
-            new_positions.insert(i, new_tile);
-        }       
-        return new_positions;
-    }
-
-    fn update_entity_map(&self, entities: &mut Vec<Entity>, entity_map: &mut HashMap<(i32, i32), HashSet<usize>>, new_positions: HashMap<usize, (i32, i32)>) {
-        entity_map.clear();
-        for (i, entity) in entities.iter_mut().enumerate() {
-            if let Some(new_tile) = new_positions.get(&i) {
-                entity.tile = *new_tile;
-                entity.tile_to_position(self.tile_width, self.tile_height);
-                entity_map.entry(entity.tile).or_insert_with(HashSet::new).insert(i);
-            }
-        }
-    }
-
-    fn update_neighbors(&self, entities: &mut Vec<Entity>, entity_map: &HashMap<(i32, i32), HashSet<usize>>) {
-        for (_i, entity) in entities.iter_mut().enumerate() {
-            // compute the tile positions of the four adjacent tiles
-            let up_tile = (entity.tile.0, (entity.tile.1 - 1 + self.grid_size.1) % self.grid_size.1);
-            let right_tile = ((entity.tile.0 + 1 + self.grid_size.0) % self.grid_size.0, entity.tile.1);
-            let down_tile = (entity.tile.0, (entity.tile.1 + 1 + self.grid_size.1) % self.grid_size.1);
-            let left_tile = ((entity.tile.0 - 1 + self.grid_size.0) % self.grid_size.0, entity.tile.1);
-
-            // use the tile positions to query the entity_map
-            entity.neighbors.up = entity_map.get(&up_tile).cloned();
-            entity.neighbors.right = entity_map.get(&right_tile).cloned();
-            entity.neighbors.down = entity_map.get(&down_tile).cloned();
-            entity.neighbors.left = entity_map.get(&left_tile).cloned();
-        }
-    }
-
-
-    fn update_sprite_frames(&self, entities: &mut Vec<Entity>) {
-        for (_i, entity) in entities.iter_mut().enumerate() {
-            if entity.movement_direction == MovementDirection::Idle {
+            if entity.animation != AnimationStyle::Directional || entity.movement_direction == MovementDirection::Idle {
                 continue;
             }
 
@@ -340,19 +289,19 @@ impl ScreenRenderer {
             // Calculate frame position based on entity state
             entity.sprite_data.frame_x =
                 frame_multiplier * frame_width_plus_one +
-                entity.sprite_data.start_frame.x() +
+                entity.sprite_data.start_frame.0 +
                 frame_width_plus_one * ((entity.sprite_data.current_frame % num_x_frames) as i32);
 
             if entity.sprite_data.current_frame % num_x_frames == 0 {
                 entity.sprite_data.frame_y =
-                    entity.sprite_data.start_frame.y() +
+                    entity.sprite_data.start_frame.1 +
                     frame_height_plus_one *
                         ((entity.sprite_data.current_frame / num_y_frames) as i32);
             }
         }
     }
 
-    pub fn debug_console_out(&mut self, debug_console: &mut DebugConsole, entities: &Vec<Entity>, entity_map: &HashMap<(i32, i32), HashSet<usize>>) {
+    pub fn debug_console_out(&mut self, debug_console: &mut DebugConsole, entities: &Vec<Entity>, entity_map: &HashMap<(i32, i32), HashSet<usize>>, rules: &RuleSet) {
         let mut debug_strings = Vec::new();
         
         debug_strings.push((DebugKey::Entity(String::from("3_Entities()")), String::from("Entities: \n=================================")));
@@ -370,6 +319,12 @@ impl ScreenRenderer {
         
         let entity_map_str = format!("Map: {:?}", entity_map);
         debug_strings.push((DebugKey::Entity("2_Map".to_string()), entity_map_str));
+
+        let mut rules_str = String::from("Rules: \n=================================");
+        for rule in &rules.rules {
+            rules_str.push_str(&format!("\n     {}", rule));
+        }
+        debug_strings.push((DebugKey::Rules("1_Rules".to_string()), rules_str));
         
         debug_console.out(debug_strings);
 
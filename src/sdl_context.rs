@@ -2,11 +2,13 @@ use std::collections::HashMap;
 
 use sdl2::image::LoadSurface;
 use sdl2::pixels::Color;
-use sdl2::render::{Canvas, Texture, BlendMode};
+use sdl2::render::{Canvas, Texture};
 use sdl2::surface::Surface;
 use sdl2::video::Window;
 use sdl2::EventPump;
 
+use crate::entity::EntityError;
+
 pub struct SdlContext {
     pub canvas: Canvas<sdl2::video::Window>,
     pub event_pump: sdl2::EventPump,
@@ -14,48 +16,34 @@ pub struct SdlContext {
 }
 
 impl SdlContext {
-    pub fn new() -> SdlContext {
+    // Opens the window and loads every sheet in `sprite_sheets`, keyed by
+    // its path
+    pub fn new(sprite_sheets: &[&str]) -> Result<SdlContext, EntityError> {
         let (event_pump, canvas) = Self::init_sdl2().unwrap();
 
-        let window_width = canvas.viewport().width();
-        let window_height = canvas.viewport().height();
-
         let texture_creator = canvas.texture_creator();
         
         let color_key = Color::RGB(84, 165, 75);
-        
-        let char_spritesheet = String::from("./assets/spritesheets/characters.png");
-        let obj_spritesheet = String::from("./assets/spritesheets/objects.png");
-        let text_spritesheet = String::from("./assets/spritesheets/text-entities.png");
-
-        let mut char_surface: Surface = LoadSurface::from_file(char_spritesheet.clone()).unwrap();
-        let mut obj_surface: Surface = LoadSurface::from_file(obj_spritesheet.clone()).unwrap();
-        let mut text_surface: Surface = LoadSurface::from_file(text_spritesheet.clone()).unwrap();
-
-        char_surface.set_color_key(true, color_key).expect("Could not set color key");
-        obj_surface.set_color_key(true, color_key).expect("Could not set color key");
-        text_surface.set_color_key(true, color_key).expect("Could not set color key");
-
-        let char_texture = texture_creator
-            .create_texture_from_surface(&char_surface)
-            .map_err(|e| e.to_string()).unwrap();
-        let obj_texture = texture_creator
-            .create_texture_from_surface(&obj_surface)
-            .map_err(|e| e.to_string()).unwrap();
-        let text_texture = texture_creator
-            .create_texture_from_surface(&text_surface)
-            .map_err(|e| e.to_string()).unwrap();
 
         let mut texture_map = HashMap::new();
-        texture_map.insert(char_spritesheet.clone(), char_texture);
-        texture_map.insert(obj_spritesheet.clone(), obj_texture);
-        texture_map.insert(text_spritesheet.clone(), text_texture);
+        for path in sprite_sheets {
+            let error = |message: String| EntityError::SpriteSheet {
+                path: path.to_string(),
+                message,
+            };
+            let mut surface: Surface = LoadSurface::from_file(path).map_err(error)?;
+            surface.set_color_key(true, color_key).map_err(error)?;
+            let texture = texture_creator
+                .create_texture_from_surface(&surface)
+                .map_err(|e| error(e.to_string()))?;
+            texture_map.insert(path.to_string(), texture);
+        }
 
-        SdlContext {
+        Ok(SdlContext {
             event_pump,
             canvas,
             texture_map
-        }
+        })
     }
 
     fn init_sdl2() -> Result<(EventPump, Canvas<sdl2::video::Window>), String> {
@@ -0,0 +1,85 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::world::MovementDirection;
+use crate::world::{Input, World};
+
+// Every turn the solver tries from each state. Undo and restart never get
+// anywhere new.
+const TURNS: [Input; 5] = [
+    Input::Move(MovementDirection::Up),
+    Input::Move(MovementDirection::Right),
+    Input::Move(MovementDirection::Down),
+    Input::Move(MovementDirection::Left),
+    Input::Wait,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solution {
+    // The shortest sequence of turns that wins the level
+    Solved(Vec<Input>),
+    // Every reachable state was explored and none of them win
+    Unsolvable,
+    // Gave up after exploring the budgeted number of states
+    OutOfBudget,
+}
+
+// Breadth-first search over the turns that can be played from `world`,
+// skipping states that were already seen. Explores at most `budget` states.
+pub fn solve(world: &World, budget: usize) -> Solution {
+    if world.is_won() {
+        return Solution::Solved(Vec::new());
+    }
+
+    // States waiting to be explored are kept as snapshots, which are a lot
+    // smaller than worlds, and copied back onto this one world for every
+    // turn tried from them
+    let mut current = world.clone();
+    current.history.clear();
+
+    // Each explored state remembers the state it came from and the turn
+    // that got it here, so the winning sequence can be walked back
+    let mut trail: Vec<(usize, Input)> = Vec::new();
+    let mut seen = HashSet::from([world.state_key()]);
+    let mut queue = VecDeque::from([(usize::MAX, world.snapshot())]);
+    let mut explored = 0;
+
+    while let Some((node, state)) = queue.pop_front() {
+        if explored == budget {
+            return Solution::OutOfBudget;
+        }
+        explored += 1;
+
+        for input in TURNS {
+            // Stepping directly rather than applying skips the undo history
+            current.restore_from(&state);
+            current.step(match input {
+                Input::Move(direction) => direction,
+                _ => MovementDirection::Idle,
+            });
+
+            if !seen.insert(current.state_key()) {
+                continue;
+            }
+
+            trail.push((node, input));
+            let next_node = trail.len() - 1;
+            if current.is_won() {
+                return Solution::Solved(turns_to(&trail, next_node));
+            }
+            queue.push_back((next_node, current.snapshot()));
+        }
+    }
+
+    Solution::Unsolvable
+}
+
+fn turns_to(trail: &[(usize, Input)], mut node: usize) -> Vec<Input> {
+    let mut turns = Vec::new();
+    while let Some((parent, input)) = trail.get(node) {
+        turns.push(*input);
+        node = *parent;
+    }
+
+    turns.reverse();
+    turns
+}
@@ -0,0 +1,780 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::entity::{Entity, EntityRepository, EntityState, Neighbors};
+use crate::rules::{RuleSet, Verb, TEXT};
+
+// How many extra times the board is read in one update while WORD objects
+// keep changing the rules
+const WORD_PASSES: usize = 4;
+
+// What happens to an entity that tries to leave the board.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeBehavior {
+    // Come back in on the opposite side
+    Wrap,
+    // Blocked, as if the edge were lined with Stop
+    Solid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MovementDirection {
+    Up,
+    Right,
+    Down,
+    Left,
+    Idle,
+}
+
+impl MovementDirection {
+    // One tile's worth of movement in this direction
+    pub fn delta(&self) -> (i32, i32) {
+        match self {
+            MovementDirection::Up => (0, -1),
+            MovementDirection::Right => (1, 0),
+            MovementDirection::Down => (0, 1),
+            MovementDirection::Left => (-1, 0),
+            MovementDirection::Idle => (0, 0),
+        }
+    }
+
+    pub fn opposite(&self) -> MovementDirection {
+        match self {
+            MovementDirection::Up => MovementDirection::Down,
+            MovementDirection::Right => MovementDirection::Left,
+            MovementDirection::Down => MovementDirection::Up,
+            MovementDirection::Left => MovementDirection::Right,
+            MovementDirection::Idle => MovementDirection::Idle,
+        }
+    }
+}
+
+// Everything a player can ask the world to do in one turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    Move(MovementDirection),
+    Wait,
+    Undo,
+    Restart,
+}
+
+// Things that happened during a turn that the game outside the world cares about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorldEvent {
+    LevelComplete,
+}
+
+// The state needed to put the world back the way it was before a turn.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub entities: Vec<Entity>,
+    pub rules: RuleSet,
+}
+
+// What `World::state_key` compares worlds by
+pub type StateKey = Vec<(u32, (i32, i32), u8)>;
+
+// The simulation state of a level, independent of any window or SDL context.
+// Everything that decides where entities end up after a turn lives here.
+#[derive(Debug, Clone)]
+pub struct World {
+    pub entities: Vec<Entity>,
+    pub entity_map: HashMap<(i32, i32), HashSet<usize>>,
+    pub grid_size: (i32, i32),
+    pub edges: EdgeBehavior,
+    pub rules: RuleSet,
+    pub history: Vec<Snapshot>,
+    // Shared between clones, which the solver makes a lot of
+    pub entity_repo: Rc<EntityRepository>,
+    initial: Rc<Snapshot>,
+    next_id: usize,
+}
+
+impl World {
+    pub fn new(
+        entities: Vec<Entity>,
+        grid_size: (i32, i32),
+        edges: EdgeBehavior,
+        entity_repo: EntityRepository,
+    ) -> World {
+        let mut world = World {
+            entities,
+            entity_map: HashMap::new(),
+            grid_size,
+            edges,
+            rules: RuleSet::default(),
+            history: Vec::new(),
+            entity_repo: Rc::new(entity_repo),
+            initial: Rc::new(Snapshot {
+                entities: Vec::new(),
+                rules: RuleSet::default(),
+            }),
+            next_id: 0,
+        };
+
+        for entity in world.entities.iter_mut() {
+            entity.id = world.next_id;
+            world.next_id += 1;
+        }
+
+        world.rebuild();
+        world.update_rules();
+        world.transform();
+        world.initial = Rc::new(world.snapshot());
+
+        world
+    }
+
+    // Play one turn the way the player would, so it can be undone
+    pub fn apply(&mut self, input: Input) -> Vec<WorldEvent> {
+        match input {
+            Input::Move(direction) => {
+                self.history.push(self.snapshot());
+                self.step(direction)
+            }
+            Input::Wait => {
+                self.history.push(self.snapshot());
+                self.step(MovementDirection::Idle)
+            }
+            Input::Undo => {
+                self.undo();
+                Vec::new()
+            }
+            Input::Restart => {
+                self.restart();
+                Vec::new()
+            }
+        }
+    }
+
+    // Advance the simulation by one turn with every You entity heading in
+    // `direction`. Unlike `apply` this leaves the undo history alone.
+    pub fn step(&mut self, direction: MovementDirection) -> Vec<WorldEvent> {
+        let mut events = Vec::new();
+
+        for entity in self.entities.iter_mut() {
+            entity.movement_direction = MovementDirection::Idle;
+            if entity.states.contains_key(&EntityState::You) {
+                entity.movement_direction = direction;
+                if direction != MovementDirection::Idle {
+                    entity.facing = direction;
+                }
+            }
+        }
+
+        // Move every You and then every Move entity, pushing what's in front of them
+        self.resolve_movement();
+
+        // Update entity neighbors
+        self.update_neighbors();
+
+        // Pick up rules formed or broken by this turn's movement
+        self.update_rules();
+        self.transform();
+
+        // Destroying and making text can change rules, so read them again afterwards
+        let destroyed = self.destroy();
+        let made = self.make();
+        if destroyed || made {
+            self.update_rules();
+        }
+
+        if self.is_won() {
+            events.push(WorldEvent::LevelComplete);
+        }
+
+        events
+    }
+
+    // A level is won when any You entity shares a tile with a Win entity on
+    // the same float layer
+    pub fn is_won(&self) -> bool {
+        self.entities
+            .iter()
+            .filter(|entity| entity.states.contains_key(&EntityState::You))
+            .any(|you| {
+                self.entity_map.get(&you.tile).is_some_and(|indices| {
+                    indices.iter().any(|idx| {
+                        let other = &self.entities[*idx];
+                        other.states.contains_key(&EntityState::Win) && other.is_floating() == you.is_floating()
+                    })
+                })
+            })
+    }
+
+    // Where everything is and which way it faces, as (name id, tile, facing)
+    // sorted so it doesn't depend on the order entities are stored in. The
+    // rest of the state follows from these, so worlds with the same key play
+    // out the same from here on.
+    pub fn state_key(&self) -> StateKey {
+        let mut key: StateKey = self
+            .entities
+            .iter()
+            .map(|entity| {
+                let name_id = self.entity_repo.name_id(&entity.name).unwrap_or(usize::MAX);
+                (name_id as u32, entity.tile, entity.facing as u8)
+            })
+            .collect();
+        key.sort_unstable();
+        key
+    }
+
+    // A hash of where everything is and which way it faces, independent of
+    // the order entities are stored in. FNV-1a rather than the std hasher so
+    // it stays the same across runs and Rust versions and can be saved.
+    pub fn state_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut entities: Vec<(&str, (i32, i32), u8)> = self
+            .entities
+            .iter()
+            .map(|entity| (entity.name.as_str(), entity.tile, entity.facing as u8))
+            .collect();
+        entities.sort();
+
+        let mut hash = FNV_OFFSET;
+        for (name, tile, facing) in entities {
+            let bytes = name
+                .bytes()
+                .chain([0])
+                .chain(tile.0.to_le_bytes())
+                .chain(tile.1.to_le_bytes())
+                .chain([facing]);
+            for byte in bytes {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+
+        hash
+    }
+
+    // Step back one turn. Does nothing at the start of the level.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(snapshot) => {
+                self.restore(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Put the level back to how it was loaded. Restarting is itself a turn,
+    // so it can be undone like any other.
+    pub fn restart(&mut self) {
+        self.history.push(self.snapshot());
+        self.restore(Snapshot::clone(&self.initial));
+    }
+
+    // Neighbors are left out, restoring works them out again
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            entities: self
+                .entities
+                .iter()
+                .map(|entity| Entity {
+                    neighbors: Neighbors::new(),
+                    ..entity.clone()
+                })
+                .collect(),
+            rules: self.rules.clone(),
+        }
+    }
+
+    // Put the board back the way it was when `snapshot` was taken
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.entities = snapshot.entities;
+        self.rules = snapshot.rules;
+        self.rebuild();
+    }
+
+    // Like restore, but copies the snapshot into the memory this world
+    // already has, for putting the same snapshot back over and over
+    pub fn restore_from(&mut self, snapshot: &Snapshot) {
+        self.entities.clone_from(&snapshot.entities);
+        self.rules.clone_from(&snapshot.rules);
+        self.rebuild();
+    }
+
+    // Recompute the entity map and neighbors from the entities' own tiles
+    fn rebuild(&mut self) {
+        self.update_entity_map();
+        self.update_neighbors();
+    }
+
+    fn update_rules(&mut self) {
+        // Objects that are WORD are the only part of how the board reads that
+        // applying rules can change
+        let word_objects = |entities: &[Entity]| -> Vec<usize> {
+            entities
+                .iter()
+                .filter(|entity| !entity.is_text() && entity.states.contains_key(&EntityState::Word))
+                .map(|entity| entity.id)
+                .collect()
+        };
+
+        let mut read_as_words = word_objects(&self.entities);
+        self.rules.reparse(&self.entities, &self.entity_map);
+        self.rules.apply(&mut self.entities, &self.entity_map);
+
+        // WORD objects take part in sentences, including the ones that make
+        // them words, so read the board again until the rules settle
+        for _ in 0..WORD_PASSES {
+            let words = word_objects(&self.entities);
+            if words == read_as_words {
+                break;
+            }
+            read_as_words = words;
+
+            let rules = RuleSet::parse(&self.entities, &self.entity_map);
+            if rules.rules == self.rules.rules {
+                break;
+            }
+            self.rules = rules;
+            self.rules.apply(&mut self.entities, &self.entity_map);
+        }
+
+        // Directional properties turn their entities whenever rules apply
+        let directions = [
+            (EntityState::Up, MovementDirection::Up),
+            (EntityState::Right, MovementDirection::Right),
+            (EntityState::Down, MovementDirection::Down),
+            (EntityState::Left, MovementDirection::Left),
+        ];
+        for entity in self.entities.iter_mut() {
+            if let Some((_, direction)) = directions.iter().find(|(state, _)| entity.states.contains_key(state)) {
+                entity.facing = *direction;
+            }
+        }
+    }
+
+    // Apply NOUN IS NOUN rules. Each entity is replaced in place by the first
+    // noun it turns into, keeping its id, and any further nouns are added on
+    // the same tile.
+    fn transform(&mut self) {
+        let mut transformed = false;
+
+        for i in 0..self.entities.len() {
+            let targets: Vec<String> = self
+                .rules
+                .transformations(i, &self.entities, &self.entity_map)
+                .into_iter()
+                .filter_map(|noun| self.archetype_for(noun, &self.entities[i]))
+                .filter(|target| *target != self.entities[i].name)
+                .collect();
+
+            for (n, target) in targets.iter().enumerate() {
+                let old = &self.entities[i];
+                let Some(mut entity) = self.entity_repo.create_entity(target, old.tile.0, old.tile.1) else {
+                    continue;
+                };
+                entity.facing = old.facing;
+
+                if n == 0 {
+                    entity.id = old.id;
+                    self.entities[i] = entity;
+                } else {
+                    self.add_entity(entity);
+                }
+                transformed = true;
+            }
+        }
+
+        if transformed {
+            self.rebuild();
+            self.update_rules();
+        }
+    }
+
+    // The archetype a rule's noun stands for when `entity` turns into it, has
+    // it or makes it. TEXT is the word that spells out the entity's own name.
+    fn archetype_for(&self, noun: &str, entity: &Entity) -> Option<String> {
+        if noun != TEXT {
+            return Some(noun.to_string());
+        }
+        if entity.is_text() {
+            return None;
+        }
+
+        self.entity_repo.text_for(&entity.name).map(|text| text.to_string())
+    }
+
+    // Put a new entity into play with a fresh id. The entity map is left for
+    // the caller to rebuild.
+    fn add_entity(&mut self, mut entity: Entity) {
+        entity.id = self.next_id;
+        self.next_id += 1;
+        self.entities.push(entity);
+    }
+
+    // Everything that `index` leaves behind for NOUN HAS NOUN when destroyed
+    fn dropped(&self, index: usize) -> Vec<Entity> {
+        let old = &self.entities[index];
+        self.rules
+            .nouns(Verb::Has, index, &self.entities, &self.entity_map)
+            .into_iter()
+            .filter_map(|noun| self.archetype_for(noun, old))
+            .filter_map(|name| self.entity_repo.create_entity(&name, old.tile.0, old.tile.1))
+            .map(|entity| Entity {
+                facing: old.facing,
+                ..entity
+            })
+            .collect()
+    }
+
+    // NOUN MAKE NOUN puts the noun on the subject's tile at the end of every
+    // turn, unless one is already there. Returns whether anything was made.
+    fn make(&mut self) -> bool {
+        if !self.rules.rules.iter().any(|rule| rule.verb == Verb::Make) {
+            return false;
+        }
+        let mut made = Vec::new();
+
+        for i in 0..self.entities.len() {
+            let entity = &self.entities[i];
+            for noun in self.rules.nouns(Verb::Make, i, &self.entities, &self.entity_map) {
+                let Some(name) = self.archetype_for(noun, entity) else {
+                    continue;
+                };
+                let present = self.entity_map.get(&entity.tile).into_iter().flatten().any(|j| {
+                    self.entities[*j].name == name
+                });
+                let queued = made.iter().any(|other: &Entity| other.tile == entity.tile && other.name == name);
+                if present || queued {
+                    continue;
+                }
+                if let Some(new_entity) = self.entity_repo.create_entity(&name, entity.tile.0, entity.tile.1) {
+                    made.push(new_entity);
+                }
+            }
+        }
+
+        if made.is_empty() {
+            return false;
+        }
+
+        for entity in made {
+            self.add_entity(entity);
+        }
+        self.rebuild();
+        true
+    }
+
+    // Resolve everything that destroys entities sharing a tile: Defeat
+    // destroys You, Hot melts Melt, Open and Shut destroy each other and Sink
+    // takes everything on its tile down with it. Floating and grounded
+    // entities on the same tile leave each other alone. Whatever the destroyed
+    // entities have is dropped where they were. Returns whether anything was
+    // destroyed.
+    fn destroy(&mut self) -> bool {
+        let destroyed = self.doomed();
+        if destroyed.is_empty() {
+            return false;
+        }
+
+        let mut dropped: Vec<usize> = destroyed.iter().copied().collect();
+        dropped.sort();
+        let dropped: Vec<Entity> = dropped.into_iter().flat_map(|i| self.dropped(i)).collect();
+
+        self.remove_entities(&destroyed);
+        for entity in dropped {
+            self.add_entity(entity);
+        }
+        self.rebuild();
+        true
+    }
+
+    // Entities that whatever shares their tile would destroy
+    pub fn doomed(&self) -> HashSet<usize> {
+        let mut destroyed = HashSet::new();
+        let entities = &self.entities;
+
+        // Most boards have nothing that destroys anything
+        let destroying = [EntityState::Defeat, EntityState::Hot, EntityState::Open, EntityState::Sink];
+        if !entities.iter().any(|entity| destroying.iter().any(|state| entity.states.contains_key(state))) {
+            return destroyed;
+        }
+
+        let layers = self.entity_map.values().flat_map(|indices| {
+            [false, true].map(|floating| {
+                indices
+                    .iter()
+                    .copied()
+                    .filter(|i| entities[*i].is_floating() == floating)
+                    .collect::<Vec<usize>>()
+            })
+        });
+        for indices in layers {
+            let with = |state: EntityState| {
+                indices
+                    .iter()
+                    .copied()
+                    .filter(move |i| entities[*i].states.contains_key(&state))
+            };
+            let has = |state: EntityState| with(state).next().is_some();
+
+            if has(EntityState::Defeat) {
+                destroyed.extend(with(EntityState::You));
+            }
+            if has(EntityState::Hot) {
+                destroyed.extend(with(EntityState::Melt));
+            }
+            if has(EntityState::Open) && has(EntityState::Shut) {
+                destroyed.extend(with(EntityState::Open).chain(with(EntityState::Shut)));
+            }
+            if has(EntityState::Sink) && indices.len() > 1 {
+                destroyed.extend(indices.iter().copied());
+            }
+        }
+
+        destroyed
+    }
+
+    // Take entities out of play. Removing shifts the indices of everything
+    // after them, so the caller has to rebuild the entity map.
+    fn remove_entities(&mut self, indices: &HashSet<usize>) {
+        let mut i = 0;
+        self.entities.retain(|_| {
+            let keep = !indices.contains(&i);
+            i += 1;
+            keep
+        });
+    }
+
+    fn board_tile(&self, tile: (i32, i32)) -> Option<(i32, i32)> {
+        board_tile(tile, self.grid_size, self.edges)
+    }
+
+    // You entities move first, in the direction the player asked for. Then
+    // every Move entity takes a step the way it faces, turning around if
+    // that way is blocked. An entity that is both moves twice. Last, Shift
+    // entities carry everything else on their tile the way they face.
+    fn resolve_movement(&mut self) {
+        for i in self.movers(EntityState::You) {
+            self.try_move(i, self.entities[i].movement_direction);
+        }
+
+        for entity in self.entities.iter_mut() {
+            if entity.states.contains_key(&EntityState::Move) {
+                entity.movement_direction = entity.facing;
+            }
+        }
+        for i in self.movers(EntityState::Move) {
+            let direction = self.entities[i].movement_direction;
+            if !self.try_move(i, direction) {
+                let reverse = direction.opposite();
+                self.entities[i].facing = reverse;
+                self.entities[i].movement_direction = reverse;
+                self.try_move(i, reverse);
+            }
+        }
+
+        let mut shifted = Vec::new();
+        for (i, entity) in self.entities.iter().enumerate() {
+            if !entity.states.contains_key(&EntityState::Shift) || entity.facing == MovementDirection::Idle {
+                continue;
+            }
+            // Sorted so replays come out the same every time
+            let mut on_tile: Vec<usize> = self.entity_map.get(&entity.tile).into_iter().flatten().copied().collect();
+            on_tile.sort();
+            for j in on_tile {
+                if j != i {
+                    shifted.push((j, entity.facing));
+                }
+            }
+        }
+        for (i, direction) in shifted {
+            self.entities[i].facing = direction;
+            self.entities[i].movement_direction = direction;
+            self.try_move(i, direction);
+        }
+    }
+
+    // Entities with `state` that are heading somewhere this turn
+    fn movers(&self, state: EntityState) -> Vec<usize> {
+        let mut movers: Vec<usize> = self
+            .entities
+            .iter()
+            .enumerate()
+            .filter(|(_, entity)| {
+                entity.movement_direction != MovementDirection::Idle && entity.states.contains_key(&state)
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        // Whoever is furthest along their direction goes first, so a line of
+        // movers heading the same way moves as one instead of blocking itself
+        movers.sort_by_key(|i| {
+            let entity = &self.entities[*i];
+            let (dx, dy) = entity.movement_direction.delta();
+            -(entity.tile.0 * dx + entity.tile.1 * dy)
+        });
+
+        movers
+    }
+
+    // Move an entity one tile, pushing the whole chain of Push entities in
+    // front of it and dragging the chain of Pull entities behind it. If the
+    // push chain runs into Stop, Pull or a solid edge nothing moves.
+    fn try_move(&mut self, index: usize, direction: MovementDirection) -> bool {
+        let Some(mut moving) = self.push_chain(index, direction) else {
+            return false;
+        };
+        moving.push(index);
+        let pulled = self.pull_chain(index, direction, &moving);
+        moving.extend(pulled);
+
+        for i in moving {
+            let tile = self.entities[i].tile;
+            let (dx, dy) = direction.delta();
+            if let Some(new_tile) = self.board_tile((tile.0 + dx, tile.1 + dy)) {
+                self.move_entity(i, new_tile);
+            }
+        }
+
+        true
+    }
+
+    // Every Push entity that has to move for `mover` to step in `direction`,
+    // or None if the move is blocked. Pull entities that aren't Push only
+    // follow from behind, from the front they are as solid as Stop.
+    fn push_chain(&self, mover: usize, direction: MovementDirection) -> Option<Vec<usize>> {
+        let (dx, dy) = direction.delta();
+        let from = self.entities[mover].tile;
+        let mut pushed = Vec::new();
+        let mut entering = vec![mover];
+        let mut tile = from;
+
+        // A chain can't be longer than the board, even when it wraps around
+        for _ in 0..(self.grid_size.0 * self.grid_size.1) {
+            tile = self.board_tile((tile.0 + dx, tile.1 + dy))?;
+            if tile == from {
+                return None;
+            }
+
+            let occupants = self.entity_map.get(&tile).cloned().unwrap_or_default();
+            let mut pushable = Vec::new();
+            for i in occupants {
+                let states = &self.entities[i].states;
+                if states.contains_key(&EntityState::Push) {
+                    pushable.push(i);
+                } else if (states.contains_key(&EntityState::Stop) || states.contains_key(&EntityState::Pull))
+                    && !self.opens(&entering, i)
+                {
+                    return None;
+                }
+            }
+
+            if pushable.is_empty() {
+                return Some(pushed);
+            }
+            pushed.extend(pushable.iter().copied());
+            entering = pushable;
+        }
+
+        None
+    }
+
+    // Every Pull entity that follows `mover` when it steps in `direction`:
+    // whatever is Pull directly behind it, then directly behind that, and so
+    // on. Pulled entities step into a tile that was just vacated, so they
+    // can't be blocked.
+    fn pull_chain(&self, mover: usize, direction: MovementDirection, moving: &[usize]) -> Vec<usize> {
+        let (dx, dy) = direction.delta();
+        let from = self.entities[mover].tile;
+        let mut pulled = Vec::new();
+        let mut tile = from;
+
+        for _ in 0..(self.grid_size.0 * self.grid_size.1) {
+            let Some(behind) = self.board_tile((tile.0 - dx, tile.1 - dy)) else {
+                break;
+            };
+            if behind == from {
+                break;
+            }
+            tile = behind;
+
+            let pullable: Vec<usize> = self
+                .entity_map
+                .get(&tile)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|i| self.entities[*i].states.contains_key(&EntityState::Pull) && !moving.contains(i))
+                .collect();
+            if pullable.is_empty() {
+                break;
+            }
+            pulled.extend(pullable);
+        }
+
+        pulled
+    }
+
+    // Whether any of `entering` can open `blocker`, letting them onto its tile
+    // so they destroy each other instead of being stopped
+    fn opens(&self, entering: &[usize], blocker: usize) -> bool {
+        let blocker = &self.entities[blocker].states;
+        entering.iter().any(|i| {
+            let states = &self.entities[*i].states;
+            (states.contains_key(&EntityState::Open) && blocker.contains_key(&EntityState::Shut))
+                || (states.contains_key(&EntityState::Shut) && blocker.contains_key(&EntityState::Open))
+        })
+    }
+
+    fn move_entity(&mut self, index: usize, tile: (i32, i32)) {
+        let old_tile = self.entities[index].tile;
+        if let Some(indices) = self.entity_map.get_mut(&old_tile) {
+            indices.remove(&index);
+            if indices.is_empty() {
+                self.entity_map.remove(&old_tile);
+            }
+        }
+
+        self.entities[index].tile = tile;
+        self.entity_map.entry(tile).or_default().insert(index);
+    }
+
+    fn update_entity_map(&mut self) {
+        // Emptied rather than dropped, as most tiles are occupied again
+        for occupants in self.entity_map.values_mut() {
+            occupants.clear();
+        }
+        for (i, entity) in self.entities.iter().enumerate() {
+            self.entity_map.entry(entity.tile).or_default().insert(i);
+        }
+        self.entity_map.retain(|_, occupants| !occupants.is_empty());
+    }
+
+    fn update_neighbors(&mut self) {
+        for entity in self.entities.iter_mut() {
+            // query the entity_map for the occupants of each surrounding tile
+            let (x, y) = entity.tile;
+            let occupants = |dx: i32, dy: i32| {
+                board_tile((x + dx, y + dy), self.grid_size, self.edges).and_then(|tile| self.entity_map.get(&tile).cloned())
+            };
+
+            entity.neighbors.up = occupants(0, -1);
+            entity.neighbors.up_right = occupants(1, -1);
+            entity.neighbors.right = occupants(1, 0);
+            entity.neighbors.down_right = occupants(1, 1);
+            entity.neighbors.down = occupants(0, 1);
+            entity.neighbors.down_left = occupants(-1, 1);
+            entity.neighbors.left = occupants(-1, 0);
+            entity.neighbors.up_left = occupants(-1, -1);
+        }
+    }
+}
+
+// The on-board tile for `tile`, or None if it's past a solid edge
+fn board_tile(tile: (i32, i32), grid_size: (i32, i32), edges: EdgeBehavior) -> Option<(i32, i32)> {
+    match edges {
+        EdgeBehavior::Wrap => Some((tile.0.rem_euclid(grid_size.0), tile.1.rem_euclid(grid_size.1))),
+        EdgeBehavior::Solid => {
+            let on_board = (0..grid_size.0).contains(&tile.0) && (0..grid_size.1).contains(&tile.1);
+            on_board.then_some(tile)
+        }
+    }
+}
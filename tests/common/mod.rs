@@ -0,0 +1,376 @@
+// Test fixtures shared by the integration tests. Not every test file uses
+// all of them.
+#![allow(dead_code)]
+
+use logibaba::entity::EntityRepository;
+use logibaba::level_map::LevelMap;
+use logibaba::world::World;
+
+// Archetypes for the tests, so they don't depend on the sprite sheets or on
+// what happens to be in assets/entities.txt
+pub const ENTITIES: &str = "
+[Logi]
+kind = object
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 1
+draw_order = 3
+
+[Goal]
+kind = object
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 1
+draw_order = 1
+
+[Rock]
+kind = object
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 1
+draw_order = 1
+
+[Wall]
+kind = object
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 1
+draw_order = 1
+
+[LogiText]
+kind = noun
+word = Logi
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[GoalText]
+kind = noun
+word = Goal
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[RockText]
+kind = noun
+word = Rock
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[WallText]
+kind = noun
+word = Wall
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[TextText]
+kind = noun
+word = Text
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Is]
+kind = operator
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Has]
+kind = operator
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Make]
+kind = operator
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[And]
+kind = operator
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Not]
+kind = operator
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[On]
+kind = operator
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Near]
+kind = operator
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Facing]
+kind = operator
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Lonely]
+kind = operator
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[You]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Win]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Push]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Pull]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Stop]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Move]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Defeat]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Sink]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Hot]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Melt]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Open]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Shut]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Up]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Down]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Left]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Right]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Shift]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Float]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+
+[Word]
+kind = property
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 2
+draw_order = 2
+states = Active
+";
+
+// Every archetype gets the same legend character in every test level
+pub const LEGEND: &str = "
+legend:
+L = Logi
+G = Goal
+R = Rock
+W = Wall
+l = LogiText
+g = GoalText
+r = RockText
+w = WallText
+t = TextText
+i = Is
+a = And
+c = Has
+b = Make
+n = Not
+o = On
+e = Near
+f = Facing
+z = Lonely
+y = You
+v = Win
+p = Push
+P = Pull
+s = Stop
+M = Move
+d = Defeat
+k = Sink
+h = Hot
+m = Melt
+u = Open
+x = Shut
+U = Up
+D = Down
+F = Left
+T = Right
+S = Shift
+H = Float
+j = Word
+X = Logi + Goal
+Z = LogiText + GoalText
+A = And + Not
+Y = You + Win
+";
+
+pub fn load(source: &str) -> World {
+    let entity_repo = EntityRepository::parse(ENTITIES).unwrap();
+    let source = source.replacen("grid:", &format!("{}\ngrid:", LEGEND), 1);
+    let level_map = LevelMap::parse(&source, &entity_repo).unwrap();
+    World::new(level_map.entities, level_map.grid_size, level_map.edges, entity_repo)
+}
+
+pub fn tile_of(world: &World, name: &str) -> (i32, i32) {
+    world.entities.iter().find(|entity| entity.name == name).unwrap().tile
+}
+
+pub fn tiles_of(world: &World, name: &str) -> Vec<(i32, i32)> {
+    let mut tiles: Vec<(i32, i32)> = world
+        .entities
+        .iter()
+        .filter(|entity| entity.name == name)
+        .map(|entity| entity.tile)
+        .collect();
+    tiles.sort();
+    tiles
+}
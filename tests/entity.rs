@@ -0,0 +1,110 @@
+use logibaba::entity::{AnimationStyle, EntityError, EntityKind, EntityRepository, EntityState};
+
+const LOGI: &str = "
+[Logi]
+kind = object
+sprite_sheet = test.png
+start_frame = 26, 1
+frames = 12
+draw_order = 3
+";
+
+// The line and message of the error `source` fails with
+fn error(source: &str) -> (usize, String) {
+    match EntityRepository::parse(source) {
+        Err(EntityError::Parse { line, message }) => (line, message),
+        Err(error) => panic!("expected a parse error, got {}", error),
+        Ok(_) => panic!("expected a parse error"),
+    }
+}
+
+#[test]
+fn parses_archetypes_with_defaults() {
+    let entity_repo = EntityRepository::parse(&format!("# Archetypes\n{}
+[LogiText]
+kind = noun
+word = Logi
+sprite_sheet = text.png
+frame_size = 32x16
+start_frame = 1, 1
+frames = 2
+draw_order = 2
+animation = static
+speed = 2.5
+states = Push, Active
+", LOGI)).unwrap();
+
+    let logi = entity_repo.create_entity("Logi", 4, 2).unwrap();
+    assert_eq!(logi.kind, EntityKind::Object);
+    assert_eq!(logi.word, "Logi");
+    assert_eq!(logi.tile, (4, 2));
+    assert!(logi.states.is_empty());
+    assert_eq!((logi.sprite_data.frame_width, logi.sprite_data.frame_height), (24, 24));
+    assert_eq!((logi.sprite_data.frame_x, logi.sprite_data.frame_y), (26, 1));
+    assert_eq!((logi.sprite_data.num_frames, logi.draw_order, logi.speed), (12, 3, 1.0));
+    assert_eq!(logi.animation, AnimationStyle::Static);
+
+    let text = entity_repo.create_entity("LogiText", 0, 0).unwrap();
+    assert_eq!(text.kind, EntityKind::Noun);
+    assert_eq!((text.sprite_data.frame_width, text.sprite_data.frame_height), (32, 16));
+    assert_eq!(text.speed, 2.5);
+    // Words start out unlit
+    assert_eq!(text.states.get(&EntityState::Push), Some(&true));
+    assert_eq!(text.states.get(&EntityState::Active), Some(&false));
+
+    assert_eq!(entity_repo.text_for("Logi"), Some("LogiText"));
+    assert!(entity_repo.contains("Logi"));
+    assert_eq!(entity_repo.sprite_sheets(), vec!["test.png", "text.png"]);
+    assert!(entity_repo.create_entity("Rock", 0, 0).is_none());
+}
+
+#[test]
+fn section_errors() {
+    assert_eq!(error("kind = object\n"), (1, "expected an [Archetype] header".to_string()));
+    assert_eq!(error("[Logi]\nkind object\n"), (2, "expected 'key = value', found 'kind object'".to_string()));
+    assert_eq!(error("[ ]\n"), (1, "'' is not a new archetype name".to_string()));
+    assert_eq!(error(&format!("{}\n[Logi]\n", LOGI)), (9, "'Logi' is not a new archetype name".to_string()));
+}
+
+#[test]
+fn missing_fields_point_at_the_header() {
+    for key in ["kind", "sprite_sheet", "start_frame", "frames", "draw_order"] {
+        let source = LOGI.lines().filter(|line| !line.starts_with(key)).collect::<Vec<&str>>().join("\n");
+        assert_eq!(error(&source), (2, format!("'Logi' is missing '{}'", key)));
+    }
+}
+
+#[test]
+fn value_errors_point_at_their_line() {
+    let with = |line: &str| format!("{}{}\n", LOGI, line);
+
+    assert_eq!(error(&with("frame_size = 24")), (8, "expected two numbers separated by 'x', found '24'".to_string()));
+    assert_eq!(error(&LOGI.replace("26, 1", "26")), (5, "expected two numbers separated by ',', found '26'".to_string()));
+    assert_eq!(error(&LOGI.replace("frames = 12", "frames = many")), (6, "expected a whole number, found 'many'".to_string()));
+    assert_eq!(error(&LOGI.replace("draw_order = 3", "draw_order = top")), (7, "expected a whole number, found 'top'".to_string()));
+    assert_eq!(error(&LOGI.replace("object", "thing")), (3, "unknown kind 'thing'".to_string()));
+    assert_eq!(error(&with("animation = spinning")), (8, "unknown animation 'spinning'".to_string()));
+    assert_eq!(
+        error(&format!("{}animation = directional\n", LOGI.replace("frames = 12", "frames = 2"))),
+        (6, "directional animation needs a multiple of 4 frames, found 2".to_string())
+    );
+    assert_eq!(error(&with("states = Push, Fly")), (8, "unknown state 'Fly'".to_string()));
+    assert_eq!(error(&with("speed = fast")), (8, "expected a number, found 'fast'".to_string()));
+    assert_eq!(error(&with("colour = green")), (8, "unknown key 'colour'".to_string()));
+    assert_eq!(error(&with("frames = 4")), (8, "'frames' is already set on line 6".to_string()));
+}
+
+#[test]
+fn properties_have_to_name_a_state() {
+    let fly = "[Fly]\nkind = property\nsprite_sheet = text.png\nstart_frame = 0, 0\nframes = 2\ndraw_order = 2\n";
+    assert_eq!(error(fly), (1, "unknown property 'Fly'".to_string()));
+    assert_eq!(error(&format!("{}word = Flying\n", fly)), (7, "unknown property 'Flying'".to_string()));
+}
+
+#[test]
+fn missing_files_report_their_path() {
+    let Err(EntityError::Io { path, .. }) = EntityRepository::load("./assets/no_such_entities.txt") else {
+        panic!("expected an io error");
+    };
+    assert_eq!(path, "./assets/no_such_entities.txt");
+}
@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+use logibaba::world::Input;
+use logibaba::{InputState, KeyRepeat, MovementDirection};
+
+const RIGHT: Input = Input::Move(MovementDirection::Right);
+const UP: Input = Input::Move(MovementDirection::Up);
+
+fn key_repeat() -> KeyRepeat {
+    KeyRepeat {
+        delay: Duration::from_millis(250),
+        interval: Duration::from_millis(100),
+    }
+}
+
+fn ms(start: Instant, millis: u64) -> Instant {
+    start + Duration::from_millis(millis)
+}
+
+#[test]
+fn every_press_is_one_turn() {
+    let start = Instant::now();
+    let mut input = InputState::new(None);
+
+    // Taps that land in the same frame still each get a turn
+    input.press(RIGHT, start);
+    input.release(RIGHT);
+    input.press(UP, start);
+    input.release(UP);
+
+    assert_eq!(input.next_turn(start), Some(RIGHT));
+    assert_eq!(input.next_turn(start), Some(UP));
+    assert_eq!(input.next_turn(ms(start, 1000)), None);
+}
+
+#[test]
+fn holding_a_key_without_repeat_is_still_one_turn() {
+    let start = Instant::now();
+    let mut input = InputState::new(None);
+
+    input.press(RIGHT, start);
+    assert_eq!(input.next_turn(start), Some(RIGHT));
+    assert_eq!(input.next_turn(ms(start, 1000)), None);
+}
+
+#[test]
+fn held_keys_repeat_after_the_delay() {
+    let start = Instant::now();
+    let mut input = InputState::new(Some(key_repeat()));
+
+    input.press(RIGHT, start);
+    assert_eq!(input.next_turn(start), Some(RIGHT));
+    assert_eq!(input.next_turn(ms(start, 200)), None);
+    assert_eq!(input.next_turn(ms(start, 250)), Some(RIGHT));
+    assert_eq!(input.next_turn(ms(start, 300)), None);
+    assert_eq!(input.next_turn(ms(start, 350)), Some(RIGHT));
+
+    input.release(RIGHT);
+    assert_eq!(input.next_turn(ms(start, 1000)), None);
+}
+
+#[test]
+fn only_the_last_key_pressed_repeats() {
+    let start = Instant::now();
+    let mut input = InputState::new(Some(key_repeat()));
+
+    input.press(RIGHT, start);
+    input.press(UP, ms(start, 100));
+    assert_eq!(input.next_turn(ms(start, 100)), Some(RIGHT));
+    assert_eq!(input.next_turn(ms(start, 100)), Some(UP));
+
+    // Letting go of the earlier key doesn't stop the one still held
+    input.release(RIGHT);
+    assert_eq!(input.next_turn(ms(start, 350)), Some(UP));
+}
+
+#[test]
+fn restart_never_repeats() {
+    let start = Instant::now();
+    let mut input = InputState::new(Some(key_repeat()));
+
+    input.press(Input::Restart, start);
+    assert_eq!(input.next_turn(start), Some(Input::Restart));
+    assert_eq!(input.next_turn(ms(start, 1000)), None);
+}
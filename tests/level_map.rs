@@ -0,0 +1,112 @@
+use logibaba::entity::EntityRepository;
+use logibaba::level_map::{LevelError, LevelMap};
+use logibaba::world::EdgeBehavior;
+
+const ENTITIES: &str = "
+[Logi]
+kind = object
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 1
+draw_order = 3
+
+[Goal]
+kind = object
+sprite_sheet = test.png
+start_frame = 0, 0
+frames = 1
+draw_order = 1
+";
+
+fn parse(source: &str) -> Result<LevelMap, LevelError> {
+    LevelMap::parse(source, &EntityRepository::parse(ENTITIES).unwrap())
+}
+
+// The line and message of the error `source` fails with
+fn error(source: &str) -> (usize, String) {
+    match parse(source) {
+        Err(LevelError::Parse { line, message }) => (line, message),
+        Err(error) => panic!("expected a parse error, got {}", error),
+        Ok(_) => panic!("expected a parse error"),
+    }
+}
+
+#[test]
+fn parses_header_legend_and_grid() {
+    let level_map = parse("
+# A comment
+title: Test
+author: Someone
+size: 3x2
+edges: solid
+
+legend:
+L = Logi
+X = Logi + Goal
+
+grid:
+L..
+..X
+").unwrap();
+
+    assert_eq!(level_map.title, "Test");
+    assert_eq!(level_map.author, "Someone");
+    assert_eq!(level_map.grid_size, (3, 2));
+    assert_eq!(level_map.edges, EdgeBehavior::Solid);
+
+    let mut entities: Vec<(&str, (i32, i32))> =
+        level_map.entities.iter().map(|entity| (entity.name.as_str(), entity.tile)).collect();
+    entities.sort();
+    assert_eq!(entities, vec![("Goal", (2, 1)), ("Logi", (0, 0)), ("Logi", (2, 1))]);
+}
+
+#[test]
+fn trailing_spaces_are_empty_tiles() {
+    let level_map = parse("size: 4x2\n\nlegend:\nL = Logi\n\ngrid:\nL   \n. L.\r\n").unwrap();
+
+    let tiles: Vec<(i32, i32)> = level_map.entities.iter().map(|entity| entity.tile).collect();
+    assert_eq!(tiles, vec![(0, 0), (2, 1)]);
+}
+
+#[test]
+fn header_errors() {
+    assert_eq!(error("size 3x1\n"), (1, "expected 'key: value', found 'size 3x1'".to_string()));
+    assert_eq!(error("\nsize: 3\n"), (2, "size should look like 12x8, found '3'".to_string()));
+    assert_eq!(error("size: 0x4\n"), (1, "size should look like 12x8, found '0x4'".to_string()));
+    assert_eq!(error("size: 3x1\nedges: bouncy\n"), (2, "edges should be wrap or solid, found 'bouncy'".to_string()));
+    assert_eq!(error("size: 3x1\ncolour: red\n"), (2, "unknown header 'colour'".to_string()));
+}
+
+#[test]
+fn legend_errors() {
+    assert_eq!(error("size: 3x1\nlegend:\nL Logi\n"), (3, "expected 'X = Name', found 'L Logi'".to_string()));
+    assert_eq!(error("size: 3x1\nlegend:\nLL = Logi\n"), (3, "legend key 'LL' should be a single character".to_string()));
+    assert_eq!(error("size: 3x1\nlegend:\n. = Logi\n"), (3, "legend key '.' should be a single character".to_string()));
+    assert_eq!(error("size: 3x1\nlegend:\nL = Logi +\n"), (3, "legend entry for 'L' is missing a name".to_string()));
+    assert_eq!(error("size: 3x1\nlegend:\nL = Logi\nL = Goal\n"), (4, "'L' is in the legend twice".to_string()));
+}
+
+#[test]
+fn grid_errors() {
+    assert_eq!(error("legend:\nL = Logi\ngrid:\nL..\n"), (4, "grid before size".to_string()));
+    assert_eq!(error("size: 3x1\nlegend:\nL = Logi\ngrid:\nL..\n...\n"), (6, "more than 1 rows in the grid".to_string()));
+    assert_eq!(error("size: 3x1\nlegend:\nL = Logi\ngrid:\nL.\n"), (5, "row is 2 tiles wide, expected 3".to_string()));
+    assert_eq!(error("size: 3x1\nlegend:\nL = Logi\ngrid:\nL.G\n"), (5, "'G' is not in the legend".to_string()));
+    assert_eq!(error("size: 3x1\nlegend:\nR = Rock\ngrid:\n.R.\n"), (5, "unknown entity 'Rock'".to_string()));
+}
+
+#[test]
+fn missing_section_errors() {
+    assert_eq!(error("title: Test\nlegend:\nL = Logi\ngrid:\n"), (1, "missing 'size' header".to_string()));
+    assert_eq!(error("size: 3x1\nlegend:\nL = Logi\n"), (3, "missing 'grid:' section".to_string()));
+    assert_eq!(error("size: 3x2\nlegend:\nL = Logi\ngrid:\nL..\n"), (4, "grid has 1 rows, expected 2".to_string()));
+}
+
+#[test]
+fn missing_files_report_their_path() {
+    let entity_repo = EntityRepository::parse(ENTITIES).unwrap();
+    let Err(LevelError::Io { path, .. }) = LevelMap::load("./assets/levels/no_such_level.txt", &entity_repo) else {
+        panic!("expected an io error");
+    };
+    assert_eq!(path, "./assets/levels/no_such_level.txt");
+}
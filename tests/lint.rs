@@ -0,0 +1,115 @@
+use logibaba::entity::EntityRepository;
+use logibaba::level_map::LevelMap;
+use logibaba::lint::{lint, lint_source, Problem};
+
+// Enough to settle the small levels here without making the shipped ones slow
+const WIN_BUDGET: usize = 2_000;
+
+fn problems(source: &str) -> Vec<(usize, String)> {
+    let entity_repo = EntityRepository::new().unwrap();
+    lint_source("test.txt", source, &entity_repo, WIN_BUDGET)
+        .into_iter()
+        .map(|Problem { line, message, .. }| (line, message))
+        .collect()
+}
+
+#[test]
+fn shipped_levels_are_clean() {
+    // The bigger levels take far more states to win than a test can afford,
+    // so running out of budget is the one problem allowed here
+    let out_of_budget = format!("no win found within {} states; raise --budget", WIN_BUDGET);
+    let entity_repo = EntityRepository::new().unwrap();
+    for level in (1..).take_while(|level| LevelMap::exists(*level)) {
+        let problems: Vec<Problem> = lint(&LevelMap::path(level), &entity_repo, WIN_BUDGET)
+            .unwrap()
+            .into_iter()
+            .filter(|problem| problem.message != out_of_budget)
+            .collect();
+        assert_eq!(problems, Vec::new());
+    }
+}
+
+#[test]
+fn levels_too_big_to_search_ask_for_more_budget() {
+    let source = "size: 5x3\nedges: solid\n\nlegend:\nL = Logi\nG = Goal\nl = LogiText\ng = GoalText\ni = Is\ny = You\nw = Win\n\ngrid:\nliy..\nL...G\ngiw..\n";
+    let entity_repo = EntityRepository::new().unwrap();
+    let problems = lint_source("test.txt", source, &entity_repo, 3);
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].line, 13);
+    assert_eq!(problems[0].message, "no win found within 3 states; raise --budget");
+
+    // The same level settles once the search can reach the goal
+    assert_eq!(lint_source("test.txt", source, &entity_repo, WIN_BUDGET), Vec::new());
+}
+
+#[test]
+fn every_unknown_name_is_reported_at_its_legend_line() {
+    let source = "size: 3x1\n\nlegend:\nL = Logi\nR = Rokc\nX = Logi + Gaol\n\ngrid:\nLRX\n";
+    assert_eq!(
+        problems(source),
+        vec![(5, "unknown entity 'Rokc'".to_string()), (6, "unknown entity 'Gaol'".to_string())]
+    );
+}
+
+#[test]
+fn typos_dont_hide_grid_mistakes() {
+    let source = "size: 3x2\n\nlegend:\nL = Logi\nR = Rokc\n\ngrid:\nLR.\n....\n";
+    assert_eq!(
+        problems(source),
+        vec![(5, "unknown entity 'Rokc'".to_string()), (9, "row is 4 tiles wide, expected 3".to_string())]
+    );
+}
+
+#[test]
+fn grid_mistakes_point_at_the_row() {
+    let source = "size: 3x1\n\nlegend:\nL = Logi\n\ngrid:\nL...\n";
+    assert_eq!(problems(source), vec![(7, "row is 4 tiles wide, expected 3".to_string())]);
+}
+
+#[test]
+fn problems_print_as_file_and_line() {
+    let entity_repo = EntityRepository::new().unwrap();
+    let problem = &lint_source("level.txt", "size: 1x1\n\ngrid:\nL\n", &entity_repo, WIN_BUDGET)[0];
+    assert_eq!(problem.to_string(), "level.txt:4: 'L' is not in the legend");
+}
+
+#[test]
+fn reports_clashes_missing_rules_and_orphaned_words() {
+    let source = "\
+size: 6x3
+edges: solid
+
+legend:
+L = Logi
+X = Logi + Goal
+l = LogiText
+g = GoalText
+i = Is
+y = You
+d = Defeat
+w = Win
+
+grid:
+liy...
+gid.w.
+X.....
+";
+    assert_eq!(
+        problems(source),
+        vec![
+            (14, "no sequence of turns reaches a win".to_string()),
+            (17, "Goal and Logi can't share the tile at column 1".to_string()),
+        ]
+    );
+
+    // Without IS the words can't say anything, so every one of them is orphaned
+    let source = "size: 3x2\n\nlegend:\nL = Logi\nl = LogiText\nw = Win\n\ngrid:\nl.w\nL..\n";
+    assert_eq!(
+        problems(source),
+        vec![
+            (8, "nothing is YOU when the level starts".to_string()),
+            (9, "LogiText at column 1 is never part of a rule: there is no IS, HAS or MAKE on the board".to_string()),
+            (9, "Win at column 3 is never part of a rule: there is no IS, HAS or MAKE on the board".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::load;
+use logibaba::replay::{Replay, ReplayError};
+use logibaba::world::Input;
+use logibaba::MovementDirection;
+
+// Logi is seven steps left of the goal
+const LEVEL: &str = "
+size: 9x3
+edges: solid
+
+grid:
+liy......
+giv......
+L......G.
+";
+
+fn record(turns: &[Input]) -> Replay {
+    let mut world = load(LEVEL);
+    let mut replay = Replay::new(1);
+    for input in turns {
+        world.apply(*input);
+        replay.record(*input);
+    }
+    replay.finish(&world);
+    replay
+}
+
+const RIGHT: Input = Input::Move(MovementDirection::Right);
+
+#[test]
+fn replays_survive_a_round_trip_through_text() {
+    let replay = record(&[RIGHT, Input::Wait, Input::Undo, Input::Move(MovementDirection::Up), Input::Restart]);
+    let text = replay.to_string();
+    assert!(text.contains("turns: RWZUX"));
+    assert_eq!(Replay::parse(&text).unwrap(), replay);
+}
+
+#[test]
+fn playback_reaches_the_recorded_state() {
+    let replay = record(&[RIGHT; 7]);
+
+    let mut world = load(LEVEL);
+    assert!(Replay::parse(&replay.to_string()).unwrap().play(&mut world));
+    assert!(world.is_won());
+}
+
+#[test]
+fn playback_flags_divergence() {
+    let mut replay = record(&[RIGHT; 7]);
+    replay.turns.pop();
+
+    let mut world = load(LEVEL);
+    assert!(!replay.play(&mut world));
+}
+
+#[test]
+fn parse_errors_point_at_the_line() {
+    let error = Replay::parse("version: 0.1.0\nlevel: 1\nhash: 00ff\nturns: RRQ\n").unwrap_err();
+    assert!(matches!(error, ReplayError::Parse { line: 4, .. }));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::load;
+use logibaba::solver::{solve, Solution};
+use logibaba::world::{Input, WorldEvent};
+use logibaba::MovementDirection;
+
+// The goal is five steps to the right, but only four to the left around
+// the wrapping edge
+const WRAP_LEVEL: &str = "
+size: 9x3
+edges: wrap
+
+grid:
+liy......
+giv......
+.L....G..
+";
+
+#[test]
+fn finds_the_shortest_win() {
+    let Solution::Solved(turns) = solve(&load(WRAP_LEVEL), 10_000) else {
+        panic!("the level should be solvable");
+    };
+    assert_eq!(turns, vec![Input::Move(MovementDirection::Left); 4]);
+}
+
+#[test]
+fn rearranges_rules_to_win() {
+    let source = "
+size: 5x4
+edges: solid
+
+grid:
+liy..
+gi.v.
+.....
+L...G
+";
+    let Solution::Solved(turns) = solve(&load(source), 10_000) else {
+        panic!("the level should be solvable");
+    };
+
+    // WIN has to be pushed into GOAL IS WIN on the way
+    let mut world = load(source);
+    let events: Vec<WorldEvent> = turns.iter().flat_map(|input| world.apply(*input)).collect();
+    assert_eq!(events, vec![WorldEvent::LevelComplete]);
+    assert_eq!(turns.len(), 10);
+}
+
+#[test]
+fn reports_levels_without_a_win() {
+    let source = "
+size: 4x2
+edges: solid
+
+grid:
+liy.
+L...
+";
+    assert_eq!(solve(&load(source), 10_000), Solution::Unsolvable);
+}
+
+#[test]
+fn gives_up_when_the_budget_runs_out() {
+    assert_eq!(solve(&load(WRAP_LEVEL), 3), Solution::OutOfBudget);
+}
+
+#[test]
+fn solves_a_medium_level_quickly() {
+    // WIN has to be pushed under GOAL IS first. Finding that explores about
+    // three thousand states, which takes well under a second in a release
+    // build and a few seconds in a debug one.
+    let source = "
+size: 7x5
+edges: solid
+
+grid:
+liy....
+..g....
+..i....
+....v..
+L.....G
+";
+    let started = Instant::now();
+    let Solution::Solved(turns) = solve(&load(source), 10_000) else {
+        panic!("the level should be solvable");
+    };
+    assert_eq!(turns.len(), 12);
+    assert!(started.elapsed() < Duration::from_secs(20), "solving took {:?}", started.elapsed());
+}
@@ -0,0 +1,979 @@
+mod common;
+
+use common::{load, tile_of, tiles_of};
+use logibaba::entity::EntityState;
+use logibaba::world::{Input, World, WorldEvent};
+use logibaba::MovementDirection;
+
+const UNDO_LEVEL: &str = "
+size: 5x5
+edges: solid
+
+grid:
+liy..
+.....
+.LG..
+gip..
+.....
+";
+
+#[test]
+fn undo_steps_back_one_turn_at_a_time() {
+    let mut world = load(UNDO_LEVEL);
+    world.apply(Input::Move(MovementDirection::Right));
+    world.apply(Input::Move(MovementDirection::Right));
+    world.apply(Input::Move(MovementDirection::Down));
+    assert_eq!((tile_of(&world, "Logi"), tile_of(&world, "Goal")), ((3, 3), (4, 2)));
+
+    world.apply(Input::Undo);
+    assert_eq!((tile_of(&world, "Logi"), tile_of(&world, "Goal")), ((3, 2), (4, 2)));
+    world.apply(Input::Undo);
+    assert_eq!((tile_of(&world, "Logi"), tile_of(&world, "Goal")), ((2, 2), (3, 2)));
+    world.apply(Input::Undo);
+    assert_eq!((tile_of(&world, "Logi"), tile_of(&world, "Goal")), ((1, 2), (2, 2)));
+
+    // Nothing left to undo
+    assert!(!world.undo());
+    assert_eq!((tile_of(&world, "Logi"), tile_of(&world, "Goal")), ((1, 2), (2, 2)));
+}
+
+#[test]
+fn undo_brings_back_broken_rules() {
+    let mut world = load(UNDO_LEVEL);
+
+    // Pushing IS out of GOAL IS PUSH breaks the rule
+    world.apply(Input::Move(MovementDirection::Down));
+    assert_eq!(tile_of(&world, "Logi"), (1, 3));
+    assert!(world.rules.rules.iter().all(|rule| rule.to_string() != "GOAL IS PUSH"));
+
+    world.apply(Input::Undo);
+    assert!(world.rules.rules.iter().any(|rule| rule.to_string() == "GOAL IS PUSH"));
+}
+
+#[test]
+fn restart_can_be_undone() {
+    let mut world = load(UNDO_LEVEL);
+    world.apply(Input::Move(MovementDirection::Right));
+    world.apply(Input::Move(MovementDirection::Right));
+
+    world.apply(Input::Restart);
+    assert_eq!((tile_of(&world, "Logi"), tile_of(&world, "Goal")), ((1, 2), (2, 2)));
+
+    world.apply(Input::Undo);
+    assert_eq!((tile_of(&world, "Logi"), tile_of(&world, "Goal")), ((3, 2), (4, 2)));
+    world.apply(Input::Undo);
+    assert_eq!((tile_of(&world, "Logi"), tile_of(&world, "Goal")), ((2, 2), (3, 2)));
+}
+
+#[test]
+fn stepping_onto_win_completes_the_level() {
+    let mut world = load("
+size: 4x3
+edges: solid
+
+grid:
+liy.
+giv.
+L.G.
+");
+    assert!(!world.is_won());
+    assert_eq!(world.apply(Input::Move(MovementDirection::Right)), Vec::new());
+    assert_eq!(world.apply(Input::Move(MovementDirection::Right)), vec![WorldEvent::LevelComplete]);
+    assert!(world.is_won());
+
+    // Waiting on the goal keeps reporting the win
+    assert_eq!(world.apply(Input::Wait), vec![WorldEvent::LevelComplete]);
+}
+
+#[test]
+fn a_level_can_start_out_won() {
+    let world = load("
+size: 3x3
+edges: solid
+
+grid:
+liy
+giv
+X..
+");
+    assert!(world.is_won());
+}
+
+const EDGE_LEVEL: &str = "
+size: 5x3
+edges: EDGES
+
+grid:
+liy..
+....L
+.....
+";
+
+#[test]
+fn wrapping_edges_bring_you_back_on_the_other_side() {
+    let mut world = load(&EDGE_LEVEL.replace("EDGES", "wrap"));
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (0, 1));
+
+    world.apply(Input::Move(MovementDirection::Left));
+    assert_eq!(tile_of(&world, "Logi"), (4, 1));
+}
+
+#[test]
+fn solid_edges_block_movement() {
+    let mut world = load(&EDGE_LEVEL.replace("EDGES", "solid"));
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (4, 1));
+
+    world.apply(Input::Move(MovementDirection::Down));
+    world.apply(Input::Move(MovementDirection::Down));
+    assert_eq!(tile_of(&world, "Logi"), (4, 2));
+}
+
+#[test]
+fn levels_set_their_own_grid_size() {
+    let mut wide = load("
+size: 20x2
+
+grid:
+liy.................
+...................L
+");
+    assert_eq!(wide.grid_size, (20, 2));
+    wide.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&wide, "Logi"), (0, 1));
+
+    let mut tall = load("
+size: 3x9
+edges: solid
+
+grid:
+l..
+i..
+y..
+...
+...
+...
+...
+...
+..L
+");
+    assert_eq!(tall.grid_size, (3, 9));
+    tall.apply(Input::Move(MovementDirection::Down));
+    assert_eq!(tile_of(&tall, "Logi"), (2, 8));
+    tall.apply(Input::Move(MovementDirection::Up));
+    assert_eq!(tile_of(&tall, "Logi"), (2, 7));
+}
+
+const PUSH_LEVEL: &str = "
+size: 4x3
+edges: EDGES
+
+grid:
+liy.
+gip.
+..LG
+";
+
+#[test]
+fn wrapping_edges_carry_pushes_around() {
+    let mut world = load(&PUSH_LEVEL.replace("EDGES", "wrap"));
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Goal"), (0, 2));
+    assert_eq!(tile_of(&world, "Logi"), (3, 2));
+}
+
+#[test]
+fn solid_edges_block_pushes() {
+    let mut world = load(&PUSH_LEVEL.replace("EDGES", "solid"));
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Goal"), (3, 2));
+    assert_eq!(tile_of(&world, "Logi"), (2, 2));
+}
+
+#[test]
+fn edges_default_to_wrapping() {
+    let mut world = load(&EDGE_LEVEL.replace("edges: EDGES\n", ""));
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (0, 1));
+}
+
+#[test]
+fn a_line_of_pushables_moves_as_a_unit() {
+    let mut world = load("
+size: 7x3
+edges: solid
+
+grid:
+liy....
+rip....
+LRRR...
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 2));
+    assert_eq!(tiles_of(&world, "Rock"), vec![(2, 2), (3, 2), (4, 2)]);
+
+    world.apply(Input::Move(MovementDirection::Right));
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tiles_of(&world, "Rock"), vec![(4, 2), (5, 2), (6, 2)]);
+
+    // The chain is against the edge now, so nobody moves
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (3, 2));
+    assert_eq!(tiles_of(&world, "Rock"), vec![(4, 2), (5, 2), (6, 2)]);
+}
+
+#[test]
+fn pushing_into_stop_blocks_the_whole_chain() {
+    let mut world = load("
+size: 6x4
+edges: wrap
+
+grid:
+liy...
+rip...
+wis...
+LRRW..
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (0, 3));
+    assert_eq!(tiles_of(&world, "Rock"), vec![(1, 3), (2, 3)]);
+    assert_eq!(tile_of(&world, "Wall"), (3, 3));
+}
+
+#[test]
+fn push_wins_over_stop_on_the_same_entity() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+rip...
+ris...
+LR....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 3));
+    assert_eq!(tile_of(&world, "Rock"), (2, 3));
+}
+
+#[test]
+fn noun_is_noun_transforms_in_place() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+rig...
+gip...
+LR....
+");
+    assert!(tiles_of(&world, "Rock").is_empty());
+    assert_eq!(tiles_of(&world, "Goal"), vec![(1, 3)]);
+
+    // The new Goal picks up Goal's rules straight away
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tiles_of(&world, "Goal"), vec![(2, 3)]);
+}
+
+#[test]
+fn noun_is_itself_prevents_transformation() {
+    let world = load("
+size: 6x4
+edges: solid
+
+grid:
+rig...
+rir...
+......
+.R....
+");
+    assert_eq!(tiles_of(&world, "Rock"), vec![(1, 3)]);
+    assert!(tiles_of(&world, "Goal").is_empty());
+}
+
+#[test]
+fn stacked_words_are_all_read() {
+    let world = load("
+size: 3x3
+edges: solid
+
+grid:
+Ziy
+i..
+v..
+");
+    let rules: Vec<String> = world.rules.rules.iter().map(|rule| rule.to_string()).collect();
+    assert_eq!(rules, vec!["TEXT IS PUSH", "LOGI IS YOU", "GOAL IS YOU", "LOGI IS WIN", "GOAL IS WIN"]);
+    assert!(world.entities.iter().filter(|entity| entity.is_text()).all(|entity| world.rules.active.contains(&entity.id)));
+}
+
+#[test]
+fn long_runs_of_stacked_words_are_read_once_per_tile() {
+    let world = load("
+size: 33x1
+edges: solid
+
+grid:
+ZAZAZAZAZAZAZAZAZAZAZAZAZAZAZAZiY
+");
+    let rules: Vec<String> = world.rules.rules.iter().map(|rule| rule.to_string()).collect();
+    assert_eq!(rules, vec!["TEXT IS PUSH", "LOGI IS YOU", "LOGI IS WIN", "GOAL IS YOU", "GOAL IS WIN"]);
+    for entity in world.entities.iter() {
+        assert_eq!(world.rules.active.contains(&entity.id), entity.name != "Not", "{}", entity.name);
+    }
+}
+
+#[test]
+fn and_expands_to_every_subject_and_target() {
+    let world = load("
+size: 9x4
+edges: solid
+
+grid:
+lagipas..
+liy......
+.........
+LG.......
+");
+    let rules: Vec<String> = world.rules.rules.iter().map(|rule| rule.to_string()).collect();
+    assert_eq!(rules, vec!["TEXT IS PUSH", "LOGI IS PUSH", "LOGI IS STOP", "GOAL IS PUSH", "GOAL IS STOP", "LOGI IS YOU"]);
+}
+
+#[test]
+fn and_between_sentences_starts_a_new_one() {
+    let mut world = load("
+size: 9x4
+edges: solid
+
+grid:
+liyagiv..
+.........
+.........
+LG.......
+");
+    let rules: Vec<String> = world.rules.rules.iter().map(|rule| rule.to_string()).collect();
+    assert_eq!(rules, vec!["TEXT IS PUSH", "LOGI IS YOU", "GOAL IS WIN"]);
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert!(world.is_won());
+}
+
+#[test]
+fn is_not_overrides_is() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+rins..
+ris...
+LR....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 3));
+}
+
+#[test]
+fn not_noun_means_every_other_object() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+nlip..
+.nnlis
+LRW...
+");
+    // Rock and Wall are pushed, double negated Logi is Stop but not Push
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 3));
+    assert_eq!(tile_of(&world, "Rock"), (2, 3));
+    assert_eq!(tile_of(&world, "Wall"), (3, 3));
+
+    let logi = world.entities.iter().find(|entity| entity.name == "Logi").unwrap();
+    assert!(logi.states.contains_key(&EntityState::Stop));
+    assert!(!logi.states.contains_key(&EntityState::Push));
+    let not_logi = world.rules.rules.iter().find(|rule| rule.subject_negated).unwrap();
+    let text = world.entities.iter().position(|entity| entity.name == "Is").unwrap();
+    assert!(!not_logi.applies_to(text, &world.entities, &world.entity_map));
+}
+
+fn has_state(world: &World, tile: (i32, i32), name: &str, state: EntityState) -> bool {
+    world
+        .entities
+        .iter()
+        .find(|entity| entity.name == name && entity.tile == tile)
+        .unwrap()
+        .states
+        .contains_key(&state)
+}
+
+#[test]
+fn on_applies_to_entities_sharing_a_tile() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+logiv.
+......
+LG....
+");
+    let rules: Vec<String> = world.rules.rules.iter().map(|rule| rule.to_string()).collect();
+    assert_eq!(rules, vec!["TEXT IS PUSH", "LOGI IS YOU", "LOGI ON GOAL IS WIN"]);
+    assert!(!has_state(&world, (0, 3), "Logi", EntityState::Win));
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert!(has_state(&world, (1, 3), "Logi", EntityState::Win));
+    assert!(world.is_won());
+}
+
+#[test]
+fn near_includes_diagonals() {
+    let world = load("
+size: 6x4
+edges: solid
+
+grid:
+relip.
+......
+R...R.
+.L....
+");
+    assert!(has_state(&world, (0, 2), "Rock", EntityState::Push));
+    assert!(!has_state(&world, (4, 2), "Rock", EntityState::Push));
+}
+
+#[test]
+fn facing_looks_at_the_next_tile_in_the_facing_direction() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+wis...
+lfsip.
+.LW...
+");
+    // Logi starts out facing right, into the Wall
+    assert!(has_state(&world, (1, 3), "Logi", EntityState::Push));
+
+    world.apply(Input::Move(MovementDirection::Left));
+    assert!(!has_state(&world, (0, 3), "Logi", EntityState::Push));
+}
+
+#[test]
+fn lonely_only_applies_to_entities_alone_on_their_tile() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+zliy..
+......
+X.....
+L.....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tiles_of(&world, "Logi"), vec![(0, 2), (1, 3)]);
+}
+
+#[test]
+fn defeat_destroys_you_on_contact() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+wid...
+......
+LW....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert!(tiles_of(&world, "Logi").is_empty());
+    assert_eq!(tiles_of(&world, "Wall"), vec![(1, 3)]);
+
+    // Undo brings Logi back
+    world.apply(Input::Undo);
+    assert_eq!(tiles_of(&world, "Logi"), vec![(0, 3)]);
+}
+
+#[test]
+fn sink_destroys_itself_and_what_enters_it() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+rip...
+wik...
+LRW...
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert!(tiles_of(&world, "Rock").is_empty());
+    assert!(tiles_of(&world, "Wall").is_empty());
+    assert_eq!(tile_of(&world, "Logi"), (1, 3));
+
+    // The entity map still lines up with the shifted indices
+    for (tile, indices) in &world.entity_map {
+        for i in indices {
+            assert_eq!(world.entities[*i].tile, *tile);
+        }
+    }
+}
+
+#[test]
+fn hot_melts_melt() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+lim...
+wih...
+LW....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert!(tiles_of(&world, "Logi").is_empty());
+}
+
+#[test]
+fn open_goes_through_shut_and_both_are_destroyed() {
+    let mut world = load("
+size: 7x4
+edges: solid
+
+grid:
+liy....
+ripau..
+wisawix
+LRW....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert!(tiles_of(&world, "Rock").is_empty());
+    assert!(tiles_of(&world, "Wall").is_empty());
+    assert_eq!(tile_of(&world, "Logi"), (1, 3));
+}
+
+#[test]
+fn destroyed_text_breaks_its_rule() {
+    let mut world = load("
+size: 6x5
+edges: solid
+
+grid:
+liy...
+rip...
+rik...
+tinp..
+LRgiv.
+");
+    assert!(world.rules.rules.iter().any(|rule| rule.to_string() == "GOAL IS WIN"));
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert!(tiles_of(&world, "GoalText").is_empty());
+    assert!(world.rules.rules.iter().all(|rule| rule.to_string() != "GOAL IS WIN"));
+}
+
+#[test]
+fn move_entities_step_every_turn_and_bounce() {
+    let mut world = load("
+size: 5x4
+edges: solid
+
+grid:
+liy..
+riM..
+.....
+L..R.
+");
+    world.apply(Input::Wait);
+    assert_eq!(tile_of(&world, "Rock"), (4, 3));
+
+    // Blocked by the edge, so it turns around and steps back the same turn
+    world.apply(Input::Wait);
+    assert_eq!(tile_of(&world, "Rock"), (3, 3));
+    assert_eq!(world.entities.iter().find(|entity| entity.name == "Rock").unwrap().facing, MovementDirection::Left);
+}
+
+#[test]
+fn move_happens_after_you() {
+    let mut world = load("
+size: 5x4
+edges: solid
+
+grid:
+liy..
+riM..
+ris..
+LR...
+");
+    // Logi goes first and is stopped by the Rock before it steps away
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (0, 3));
+    assert_eq!(tile_of(&world, "Rock"), (2, 3));
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 3));
+    assert_eq!(tile_of(&world, "Rock"), (3, 3));
+}
+
+#[test]
+fn pull_drags_the_chain_behind_a_mover() {
+    let mut world = load("
+size: 7x4
+edges: solid
+
+grid:
+liy....
+riP....
+wiP....
+WRL....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (3, 3));
+    assert_eq!(tile_of(&world, "Rock"), (2, 3));
+    assert_eq!(tile_of(&world, "Wall"), (1, 3));
+
+    // Walking away at right angles leaves the chain where it is
+    world.apply(Input::Move(MovementDirection::Up));
+    assert_eq!(tile_of(&world, "Rock"), (2, 3));
+}
+
+#[test]
+fn pull_blocks_from_the_front() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+giP...
+.LG...
+......
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 2));
+    assert_eq!(tile_of(&world, "Goal"), (2, 2));
+
+    // From the other side it follows along
+    world.apply(Input::Move(MovementDirection::Down));
+    world.apply(Input::Move(MovementDirection::Right));
+    world.apply(Input::Move(MovementDirection::Right));
+    world.apply(Input::Move(MovementDirection::Up));
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (4, 2));
+    assert_eq!(tile_of(&world, "Goal"), (3, 2));
+}
+
+#[test]
+fn pull_and_push_together() {
+    let mut world = load("
+size: 7x4
+edges: solid
+
+grid:
+liy....
+riP....
+wip....
+.RLW...
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Wall"), (4, 3));
+    assert_eq!(tile_of(&world, "Logi"), (3, 3));
+    assert_eq!(tile_of(&world, "Rock"), (2, 3));
+}
+
+#[test]
+fn blocked_movers_pull_nothing() {
+    let mut world = load("
+size: 7x4
+edges: solid
+
+grid:
+liy....
+riP....
+wis....
+RLW....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 3));
+    assert_eq!(tile_of(&world, "Rock"), (0, 3));
+}
+
+#[test]
+fn directional_properties_set_facing() {
+    let mut world = load("
+size: 5x5
+edges: solid
+
+grid:
+liy..
+riUaM
+.....
+.....
+L..R.
+");
+    let rock = world.entities.iter().find(|entity| entity.name == "Rock").unwrap();
+    assert_eq!(rock.facing, MovementDirection::Up);
+
+    world.apply(Input::Wait);
+    assert_eq!(tile_of(&world, "Rock"), (3, 3));
+    world.apply(Input::Wait);
+    assert_eq!(tile_of(&world, "Rock"), (3, 2));
+}
+
+#[test]
+fn shift_carries_what_is_on_it() {
+    let mut world = load("
+size: 6x5
+edges: solid
+
+grid:
+liy...
+wiSaD.
+......
+LW....
+......
+");
+    // Logi steps onto the Wall and is carried down off it the same turn
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 4));
+    assert_eq!(tile_of(&world, "Wall"), (1, 3));
+    assert_eq!(world.entities.iter().find(|entity| entity.name == "Logi").unwrap().facing, MovementDirection::Down);
+}
+
+#[test]
+fn float_keeps_layers_from_interacting() {
+    let mut world = load("
+size: 6x5
+edges: solid
+
+grid:
+liy...
+giv...
+gid...
+liH...
+LG....
+");
+    // Floating Logi isn't defeated by the grounded Goal, but can't win on it either
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 4));
+    assert!(!world.is_won());
+}
+
+#[test]
+fn float_matches_float() {
+    let mut world = load("
+size: 6x5
+edges: solid
+
+grid:
+liy...
+giv...
+liH...
+giH...
+LG....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert!(world.is_won());
+}
+
+#[test]
+fn has_drops_its_noun_when_destroyed() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+lcr...
+wid...
+LW....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert!(tiles_of(&world, "Logi").is_empty());
+    assert_eq!(tiles_of(&world, "Rock"), vec![(1, 3)]);
+}
+
+#[test]
+fn make_spawns_its_noun_once_per_tile() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+lbr...
+......
+L.....
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tiles_of(&world, "Rock"), vec![(1, 3)]);
+
+    world.apply(Input::Wait);
+    assert_eq!(tiles_of(&world, "Rock"), vec![(1, 3)]);
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tiles_of(&world, "Rock"), vec![(1, 3), (2, 3)]);
+
+    world.apply(Input::Undo);
+    assert_eq!(tiles_of(&world, "Rock"), vec![(1, 3)]);
+}
+
+#[test]
+fn entity_ids_stay_unique_and_stable() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+lcr...
+wid...
+LWG...
+");
+    let old_ids: Vec<usize> = world.entities.iter().map(|entity| entity.id).collect();
+    let goal_id = world.entities.iter().find(|entity| entity.name == "Goal").unwrap().id;
+
+    // Logi is destroyed and drops a Rock, which shifts everything after it
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(world.entities.iter().find(|entity| entity.name == "Goal").unwrap().id, goal_id);
+
+    let rock_id = world.entities.iter().find(|entity| entity.name == "Rock").unwrap().id;
+    assert!(!old_ids.contains(&rock_id));
+
+    let mut ids: Vec<usize> = world.entities.iter().map(|entity| entity.id).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), world.entities.len());
+}
+
+#[test]
+fn text_is_pushable_by_default() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+......
+......
+Lrip..
+");
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "Logi"), (1, 3));
+    assert_eq!(tile_of(&world, "RockText"), (2, 3));
+    assert_eq!(tile_of(&world, "Push"), (4, 3));
+}
+
+#[test]
+fn text_noun_refers_to_every_word() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+liy...
+tis...
+......
+Lr....
+");
+    // Every word picks up Stop, and Push still wins when one is pushed
+    let rock_text = world.entities.iter().find(|entity| entity.name == "RockText").unwrap();
+    assert!(rock_text.states.contains_key(&EntityState::Stop));
+    assert!(rock_text.states.contains_key(&EntityState::Push));
+
+    world.apply(Input::Move(MovementDirection::Right));
+    assert_eq!(tile_of(&world, "RockText"), (2, 3));
+}
+
+#[test]
+fn noun_is_text_turns_objects_into_their_word() {
+    let world = load("
+size: 6x4
+edges: solid
+
+grid:
+rit...
+......
+......
+.R....
+");
+    assert!(tiles_of(&world, "Rock").is_empty());
+    assert_eq!(tiles_of(&world, "RockText"), vec![(0, 0), (1, 3)]);
+}
+
+#[test]
+fn word_objects_read_as_their_name() {
+    let world = load("
+size: 6x4
+edges: solid
+
+grid:
+rij...
+......
+......
+Riv...
+");
+    let rules: Vec<String> = world.rules.rules.iter().map(|rule| rule.to_string()).collect();
+    assert_eq!(rules, vec!["TEXT IS PUSH", "ROCK IS WORD", "ROCK IS WIN"]);
+    assert!(has_state(&world, (0, 3), "Rock", EntityState::Win));
+}
+
+fn is_active(world: &World, name: &str) -> bool {
+    world.entities.iter().find(|entity| entity.name == name).unwrap().states[&EntityState::Active]
+}
+
+#[test]
+fn words_in_valid_sentences_are_active() {
+    let mut world = load("
+size: 6x4
+edges: solid
+
+grid:
+......
+liy...
+g.....
+.Lp...
+");
+    assert!(is_active(&world, "LogiText"));
+    assert!(is_active(&world, "You"));
+    assert!(!is_active(&world, "GoalText"));
+    assert!(!is_active(&world, "Push"));
+
+    // Push You out of line from below to break LOGI IS YOU
+    world.apply(Input::Move(MovementDirection::Up));
+    world.apply(Input::Move(MovementDirection::Right));
+    world.apply(Input::Move(MovementDirection::Up));
+    assert!(!is_active(&world, "LogiText"));
+    assert!(!is_active(&world, "Is"));
+}
+
+#[test]
+fn only_applied_turns_can_be_undone() {
+    let mut world = load(&EDGE_LEVEL.replace("EDGES", "solid"));
+
+    world.apply(Input::Move(MovementDirection::Down));
+    world.step(MovementDirection::Left);
+    assert_eq!(tile_of(&world, "Logi"), (3, 2));
+    assert_eq!(world.history.len(), 1);
+
+    world.apply(Input::Undo);
+    assert_eq!(tile_of(&world, "Logi"), (4, 1));
+}